@@ -1,14 +1,421 @@
-use std::path::PathBuf;
+//! Command line arguments, and the config file filling in what's not given there.
 
-use clap::Parser;
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind as IoErrorKind,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use eyre::{Context, Result};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{
+    state::{export::Scope, fetch::Source, filter::Criterion, link::Opener, timezone::TimeZone},
+    ui::{
+        keymap::{Binding, Preset},
+        Theme,
+    },
+};
+
+/// Commented version of all settings the config file knows about, with their defaults.
+pub const TEMPLATE: &str = include_str!("../data/config.toml");
 
 #[derive(Parser, Debug)]
 pub struct Config {
-    /// XML file of the schedule.
-    /// Download it from <https://fahrplan.events.ccc.de/congress/2023/fahrplan/schedule.xml> if not done yet.
-    pub schedule: PathBuf,
+    /// TOML file to read defaults from.
+    /// Defaults to `inoe/config.toml` in the user's config directory.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// XML or JSON file of the schedule, or an `http(s)://` URL to download it from.
+    /// For example <https://fahrplan.events.ccc.de/congress/2023/fahrplan/schedule.xml>.
+    #[arg(global = true)]
+    pub schedule: Option<Source>,
+
+    /// Directory to keep downloaded and parsed schedules in.
+    /// Defaults to `inoe/schedules` in the user's cache directory.
+    #[arg(long, global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// File to store starred events in, one guid per line.
+    /// Defaults to `inoe/favourites` in the user's data directory. Without one, favourites are only
+    /// kept until quitting.
+    #[arg(long, global = true)]
+    pub favourites: Option<PathBuf>,
+
+    /// Only show events where the field has the value, e.g. `track=Security`.
+    /// Can be given multiple times. Possible fields are track, room, language and type.
+    #[arg(long = "filter", value_name = "FIELD=VALUE", global = true)]
+    pub filters: Vec<Criterion>,
+
+    /// Timezone to display times in, also deciding what counts as "today".
+    /// Either `conference` for the offsets the schedule gives its events, `local` for the system
+    /// one, `utc` or an offset like `+01:00`. Timezone names in the schedule aren't looked up.
+    /// Defaults to `conference`.
+    #[arg(long)]
+    pub timezone: Option<TimeZone>,
+
+    /// Reload the schedule on its own in this interval, e.g. `15min`.
+    /// If anything changed, the changes are shown right away.
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub reload_every: Option<Duration>,
+
+    /// Directory to write calendar files to when exporting from within the viewer.
+    /// Defaults to the current directory.
+    #[arg(long, default_value = ".")]
+    pub export_dir: PathBuf,
+
+    /// Colours to use, `dark` by default.
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+
+    /// How many columns the grid spreads events over, unless it shows one column per room.
+    /// Defaults to 7.
+    #[arg(long, value_name = "COUNT")]
+    pub grid_columns: Option<NonZeroUsize>,
+
+    /// Which keys are bound to begin with, `vim` by default.
+    #[arg(long, value_enum)]
+    pub keymap: Option<Preset>,
+
+    /// Command to open links with, the URL is appended as last argument, e.g. `firefox`.
+    /// Defaults to `xdg-open`, or `open` on macOS.
+    #[arg(long, value_name = "COMMAND")]
+    pub opener: Option<Opener>,
+
+    /// Keys bound in the config file, taking precedence over the ones of the keymap.
+    #[arg(skip)]
+    pub keys: Vec<Binding>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Write events into a calendar file instead of opening the viewer.
+    Export(Export),
+    /// Show where the config file is read from.
+    Config(ShowConfig),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Export {
+    #[arg(long, value_enum, default_value_t = Format::Ics)]
+    pub format: Format,
+
+    /// Which events to export.
+    #[arg(long, value_enum, default_value_t = Scope::Favourites)]
+    pub scope: Scope,
+
+    /// File to write to. Defaults to standard output.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ShowConfig {
+    /// Print a commented config file with all settings and their defaults instead.
+    #[arg(long)]
+    pub print_default: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Format {
+    /// iCalendar, as understood by about every calendar application.
+    Ics,
+}
+
+impl Config {
+    /// Where the config file is read from, if any.
+    pub fn file_path(&self) -> Option<PathBuf> {
+        match &self.config {
+            Some(path) => Some(path.clone()),
+            None => default_file_path(),
+        }
+    }
+
+    /// Fills in everything not given on the command line from the config file.
+    fn merge(&mut self, file: File) {
+        self.schedule = self.schedule.take().or(file.schedule);
+        self.timezone = self.timezone.or(file.timezone);
+        self.favourites = self.favourites.take().or(file.favourites);
+        self.theme = self.theme.or(file.theme);
+        self.grid_columns = self.grid_columns.or(file.grid_columns);
+        self.keymap = self.keymap.or(file.keymap);
+        self.opener = self.opener.take().or(file.opener);
+        self.keys = file.keys;
+    }
+}
+
+/// Everything that can be set in the config file. See [`TEMPLATE`] for what each one does.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct File {
+    #[serde(deserialize_with = "parsed")]
+    schedule: Option<Source>,
+    #[serde(deserialize_with = "parsed")]
+    timezone: Option<TimeZone>,
+    favourites: Option<PathBuf>,
+    theme: Option<Theme>,
+    grid_columns: Option<NonZeroUsize>,
+    keymap: Option<Preset>,
+    #[serde(deserialize_with = "parsed")]
+    opener: Option<Opener>,
+    #[serde(deserialize_with = "keys")]
+    keys: Vec<Binding>,
+}
+
+/// One entry of the `[keys]` table, which is either a key bound everywhere, or a table of keys
+/// only bound in one mode or the overlays.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeysEntry {
+    Global(String),
+    Scoped(HashMap<String, String>),
 }
 
-pub fn parse() -> Config {
-    Config::parse()
+impl File {
+    /// Reads the config file at `path`. A missing file is only fine if the user didn't ask for
+    /// that file explicitly.
+    fn load(path: &Path, explicit: bool) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == IoErrorKind::NotFound && !explicit => {
+                return Ok(Self::default())
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("could not read config file {}", path.display()))
+            }
+        };
+
+        toml::from_str(&content)
+            .with_context(|| format!("could not parse config file {}", path.display()))
+    }
+}
+
+/// Where the config file is if not overridden.
+fn default_file_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("inoe").join("config.toml"))
+}
+
+/// Deserializes a string using the type's [`FromStr`] impl, the same one clap uses.
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(de::Error::custom)
+}
+
+fn keys<'de, D>(deserializer: D) -> Result<Vec<Binding>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let binding = |scope, chord: &str, command: &str| -> Result<Binding, String> {
+        Ok(Binding {
+            scope,
+            chord: chord.parse()?,
+            command: command.parse()?,
+        })
+    };
+
+    let mut bindings = Vec::new();
+    for (key, entry) in HashMap::<String, KeysEntry>::deserialize(deserializer)? {
+        match entry {
+            KeysEntry::Global(command) => bindings.push(binding(None, &key, &command)),
+            KeysEntry::Scoped(entries) => {
+                let scope = key.parse().map_err(de::Error::custom)?;
+                bindings.extend(
+                    entries
+                        .iter()
+                        .map(|(chord, command)| binding(Some(scope), chord, command)),
+                );
+            }
+        }
+    }
+
+    let mut bindings = bindings
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(de::Error::custom)?;
+
+    // scoped ones last, so a global one can't override them by accident
+    bindings.sort_by_key(|binding| binding.scope.is_some());
+    Ok(bindings)
+}
+
+pub fn parse() -> Result<Config> {
+    let mut config = Config::parse();
+
+    // looking at the config file shouldn't fail because of the config file
+    if let Some(Command::Config(_)) = config.command {
+        return Ok(config);
+    }
+
+    if let Some(path) = config.file_path() {
+        let file = File::load(&path, config.config.is_some())?;
+        config.merge(file);
+    }
+
+    // can't be marked as required since it's global, but everything needs a schedule
+    if config.schedule.is_none() {
+        Config::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the schedule to look at is required",
+            )
+            .exit();
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::store::Mode,
+        ui::keymap::{Command as KeyCommand, KeyChord, Keymap, Scope},
+    };
+
+    #[test]
+    fn keys_are_bound_globally_and_per_mode() {
+        let file: File = toml::from_str(
+            r#"
+            [keys]
+            x = "quit"
+            ctrl-r = "reload"
+
+            [keys.single]
+            x = "back"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.keys.len(), 3);
+        assert!(file.keys.contains(&Binding {
+            scope: None,
+            chord: KeyChord::ctrl('r'),
+            command: KeyCommand::Reload,
+        }));
+        // whatever order the table came in, the scoped ones are last
+        assert_eq!(file.keys[2].scope, Some(Scope::Mode(Mode::Single)));
+
+        let keymap = Keymap::new(Preset::Vim, &file.keys);
+        assert_eq!(
+            keymap.get(Mode::Single, KeyChord::char('x')),
+            Some(KeyCommand::Back)
+        );
+        assert_eq!(
+            keymap.get(Mode::Grid, KeyChord::char('x')),
+            Some(KeyCommand::Quit)
+        );
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        for keys in [
+            "[keys]\nx = \"fly\"",
+            "[keys]\nhyper-x = \"quit\"",
+            "[keys]\nx = 1",
+            "[keys.nowhere]\nx = \"quit\"",
+            "[keys.single]\nx = 1",
+        ] {
+            assert!(toml::from_str::<File>(keys).is_err(), "{keys}");
+        }
+    }
+
+    fn parse(args: &[&str]) -> Config {
+        Config::try_parse_from(["inoe"].iter().chain(args)).unwrap()
+    }
+
+    fn path(path: &str) -> Option<Source> {
+        Some(Source::Path(path.into()))
+    }
+
+    #[test]
+    fn schedule_is_given_with_or_without_subcommand() {
+        let config = parse(&["schedule.xml"]);
+        assert_eq!(config.schedule, path("schedule.xml"));
+        assert!(config.command.is_none());
+
+        for args in [
+            &["export", "schedule.xml"][..],
+            &["schedule.xml", "export"],
+            &[
+                "export",
+                "--scope",
+                "filtered",
+                "schedule.xml",
+                "-o",
+                "out.ics",
+            ],
+        ] {
+            let config = parse(args);
+            assert_eq!(config.schedule, path("schedule.xml"), "{args:?}");
+            assert!(
+                matches!(config.command, Some(Command::Export(_))),
+                "{args:?}"
+            );
+        }
+
+        // left to the config file
+        let mut config = parse(&["export"]);
+        assert_eq!(config.schedule, None);
+        config.merge(toml::from_str(r#"schedule = "schedule.xml""#).unwrap());
+        assert_eq!(config.schedule, path("schedule.xml"));
+    }
+
+    #[test]
+    fn command_line_beats_config_file_beats_defaults() {
+        let mut config = parse(&["other.xml", "--timezone", "utc", "--theme", "light"]);
+        config.merge(
+            toml::from_str(
+                r#"
+                schedule = "schedule.xml"
+                timezone = "+02:00"
+                theme = "monochrome"
+                grid-columns = 3
+                "#,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(config.schedule, path("other.xml"));
+        assert_eq!(config.timezone, Some(TimeZone::Fixed(time::UtcOffset::UTC)));
+        assert_eq!(config.theme, Some(Theme::Light));
+        assert_eq!(config.grid_columns, NonZeroUsize::new(3));
+        // the defaults are filled in where they're used
+        assert_eq!(config.keymap, None);
+        assert_eq!(config.favourites, None);
+    }
+
+    #[test]
+    fn arguments_are_consistent() {
+        // checks every subcommand, not only the ones the tests above happen to parse
+        Config::command().debug_assert();
+    }
+
+    #[test]
+    fn overlay_keys_are_bound_on_their_own() {
+        let file: File = toml::from_str("[keys.overlay]\nx = \"toggle\"").unwrap();
+        let keymap = Keymap::new(Preset::Vim, &file.keys);
+
+        assert_eq!(
+            keymap.get(Scope::Overlay, KeyChord::char('x')),
+            Some(KeyCommand::Toggle)
+        );
+        assert_eq!(
+            keymap.get(Mode::Grid, KeyChord::char('x')),
+            Some(KeyCommand::ExportFavourites)
+        );
+    }
 }
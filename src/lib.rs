@@ -27,58 +27,183 @@ pub mod config;
 pub mod state;
 pub mod ui;
 
-use eyre::{Context, Result};
-use state::{store::Mode, Dispatcher};
+use std::{
+    fs,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use config::{Command, Config, Export, Format, ShowConfig};
+use eyre::{Context, ContextCompat, Result};
+use state::{
+    export::Scope,
+    filter::Criterion,
+    schedule::{EventId, PersonId},
+    store::{Loaded, Mode, State},
+    Dispatcher,
+};
 use ui::Ui;
 
 pub type DateTime = time::OffsetDateTime;
 
 pub fn run() -> Result<()> {
-    let app = App::new()?;
-    app.run()
+    let config = config::parse()?;
+
+    match &config.command {
+        Some(Command::Export(export)) => run_export(&config, export),
+        Some(Command::Config(show)) => show_config(&config, show),
+        None => {
+            let app = App::new(&config)?;
+            app.run()
+        }
+    }
+}
+
+/// Writes the requested events into a file or stdout, without ever opening the viewer.
+fn run_export(config: &Config, export: &Export) -> Result<()> {
+    let state = State::new(config)?;
+    let content = match export.format {
+        Format::Ics => state.export(export.scope),
+    };
+
+    match &export.output {
+        Some(path) => fs::write(path, content).context("export writing failure"),
+        None => io::stdout()
+            .write_all(content.as_bytes())
+            .context("export writing failure"),
+    }
+}
+
+/// Prints where the config file is read from, or a template for it.
+fn show_config(config: &Config, show: &ShowConfig) -> Result<()> {
+    let content = match show.print_default {
+        true => config::TEMPLATE.to_string(),
+        false => {
+            let path = config
+                .file_path()
+                .context("could not find a config directory")?;
+            match path.exists() {
+                true => format!("{}\n", path.display()),
+                false => format!("{} (doesn't exist yet)\n", path.display()),
+            }
+        }
+    };
+
+    io::stdout()
+        .write_all(content.as_bytes())
+        .context("config writing failure")
 }
 
 pub struct App {
     ui: Ui,
     dispatcher: Dispatcher,
+    /// How often to reload the schedule on its own, if at all.
+    reload_every: Option<Duration>,
+    last_reload: Instant,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let config = config::parse();
-        let dispatcher = Dispatcher::new(&config)?;
-        let ui = Ui::new().context("ui creation failure")?;
+    pub fn new(config: &Config) -> Result<Self> {
+        let dispatcher = Dispatcher::new(config)?;
+        let ui = Ui::new(config).context("ui creation failure")?;
 
-        // could store config in app if needed
-        Ok(Self { ui, dispatcher })
+        Ok(Self {
+            ui,
+            dispatcher,
+            reload_every: config.reload_every,
+            last_reload: Instant::now(),
+        })
     }
 
     pub fn run(mut self) -> Result<()> {
+        let result = self.event_loop();
+
+        // the terminal has to be usable again, no matter how the loop ended
+        self.ui.clean_up()?;
+        result?;
+        self.dispatcher.store.save_session()?;
+        Ok(())
+    }
+
+    /// Draws frames and dispatches actions until the user exits or something fails.
+    fn event_loop(&mut self) -> Result<()> {
         loop {
             let state = self.dispatcher.store.state();
-            let action = self.ui.frame(state)?;
+            let action = self
+                .ui
+                .frame(state)?
+                .or_else(|| self.dispatcher.poll())
+                .or_else(|| self.reload_if_due());
 
             if let Some(action) = action {
                 let should_exit = matches!(action, Action::Exit);
-                self.dispatcher.dispatch(action);
+                self.dispatcher.dispatch(action)?;
 
                 if should_exit {
-                    break;
+                    return Ok(());
                 }
             }
         }
+    }
 
-        self.ui.clean_up()?;
-        Ok(())
+    /// Returns a quiet reload if the interval given by the user passed since the last one.
+    fn reload_if_due(&mut self) -> Option<Action> {
+        let interval = self.reload_every?;
+        if self.last_reload.elapsed() < interval {
+            return None;
+        }
+
+        self.last_reload = Instant::now();
+        Some(Action::Reload { quiet: true })
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub enum Action {
     Exit,
     Select(To),
+    /// Select the given event directly, e.g. because it was clicked on.
+    SelectEvent(EventId),
     SwitchTo(Mode),
+    /// Leave the single mode for the mode it was entered from, or any other mode for the grid.
+    Back,
     Scroll(VerticalDirection),
+    /// Star or unstar the selected event.
+    ToggleFavourite,
+    /// Show or hide events with the given value, see [`state::filter`].
+    ToggleFilter(Criterion),
+    /// Show all events again.
+    ClearFilter,
+    /// Switch between packing events into as few columns as possible and one column per room.
+    ToggleGridLayout,
+    /// Look for events containing the given text and list them.
+    Search(String),
+    /// Select the next ([`VerticalDirection::Down`]) or previous search match, wrapping around.
+    CycleMatch(VerticalDirection),
+    /// Load the schedule again and show what changed.
+    /// `quiet` reloads only switch to the changes view if anything actually changed.
+    Reload {
+        quiet: bool,
+    },
+    /// The outcome of the last [`Action::Reload`], either the new schedule or why loading it
+    /// failed.
+    Reloaded {
+        quiet: bool,
+        schedule: Loaded,
+    },
+    /// Select the first visible event of the next ([`VerticalDirection::Down`]) or previous
+    /// conference day.
+    JumpDay(VerticalDirection),
+    /// Write the events in the given scope into a calendar file in the export directory.
+    Export(Scope),
+    /// Open the _n_-th URL of the selected event, counting from 1, see
+    /// [`state::schedule::Event::urls`].
+    OpenLink(usize),
+    /// List everyone speaking with the given person selected, see [`state::store::Mode::Speakers`].
+    ShowSpeaker(PersonId),
+    /// Only list speakers whose name fuzzily matches the given text, see
+    /// [`state::schedule::Schedule::persons`].
+    FilterSpeakers(String),
 }
 
 /// Direction but not since the "direction" is taken by ratatui already.
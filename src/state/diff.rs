@@ -0,0 +1,132 @@
+//! Finding out what changed between two versions of the same schedule.
+
+use super::schedule::{Event, EventId, Schedule};
+
+/// All differences between an old and a new [`Schedule`], each list in chronological order.
+#[derive(Clone, Debug, Default)]
+pub struct ScheduleDiff {
+    /// Only in the new schedule.
+    pub added: Vec<EventId>,
+    /// Only in the old schedule, so they're kept here as they can't be looked up anymore.
+    pub removed: Vec<Event>,
+    pub changed: Vec<Change>,
+}
+
+/// One event that exists in both schedules, but differs in ways the user should know about.
+#[derive(Clone, Debug)]
+pub struct Change {
+    /// The event as it was before, it can be looked up for the new version.
+    pub old: Event,
+    pub rescheduled: bool,
+    pub moved_room: bool,
+    pub renamed: bool,
+}
+
+impl ScheduleDiff {
+    pub fn between(old: &Schedule, new: &Schedule) -> Self {
+        let added = new
+            .coords()
+            .filter(|(_, event)| old.get(&event.id).is_none())
+            .map(|(_, event)| event.id)
+            .collect();
+
+        let removed = old
+            .coords()
+            .filter(|(_, event)| new.get(&event.id).is_none())
+            .map(|(_, event)| event.clone())
+            .collect();
+
+        let changed = new
+            .coords()
+            .filter_map(|(_, event)| {
+                let before = old.get(&event.id)?;
+                let change = Change {
+                    old: before.clone(),
+                    rescheduled: before.start != event.start || before.duration != event.duration,
+                    moved_room: before.room != event.room,
+                    renamed: before.title != event.title,
+                };
+                change.is_relevant().then_some(change)
+            })
+            .collect();
+
+        Self {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Change {
+    fn is_relevant(&self) -> bool {
+        self.rescheduled || self.moved_room || self.renamed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture::{self, event};
+
+    #[test]
+    fn same_schedule_has_no_changes() {
+        let diff = ScheduleDiff::between(&fixture::schedule(), &fixture::edited(|_| ()));
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn everything_relevant_is_found() {
+        let new = fixture::edited(|conference| {
+            let day = &mut conference["days"][0]["rooms"];
+            day["Saal 1"][1]["date"] = "2023-12-27T14:00:00+01:00".into();
+            day["Saal 1"][2]["room"] = "Saal 2".into();
+            day["Saal 1"][2]["abstract"] = "Not relevant enough to tell.".into();
+            day["Saal 2"][0]["title"] = "Degenerative Art".into();
+
+            let late = day["Saal 1"].as_array_mut().unwrap().remove(3);
+            let mut added = late.clone();
+            added["guid"] = "00000000-0000-4000-8000-000000000007".into();
+            added["date"] = "2023-12-28T14:00:00+01:00".into();
+            conference["days"][1]["rooms"]["Saal 1"]
+                .as_array_mut()
+                .unwrap()
+                .push(added);
+        });
+
+        let diff = ScheduleDiff::between(&fixture::schedule(), &new);
+
+        assert_eq!(diff.added, [event(7)]);
+        let removed: Vec<_> = diff.removed.iter().map(|event| event.id).collect();
+        assert_eq!(removed, [event(4)]);
+
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|change| {
+                (
+                    change.old.id,
+                    change.rescheduled,
+                    change.moved_room,
+                    change.renamed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                (event(5), false, false, true),
+                (event(3), false, true, false),
+                (event(2), true, false, false),
+            ]
+        );
+        assert_eq!(
+            diff.changed[2].old.start,
+            fixture::schedule()[&event(2)].start
+        );
+    }
+}
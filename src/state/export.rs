@@ -0,0 +1,171 @@
+//! Writing events into iCalendar files, so they end up in phone calendars and the like.
+//!
+//! Each event's guid is used as its `UID`, so importing a newer export updates the events from an
+//! older one instead of duplicating them.
+
+use clap::ValueEnum;
+use time::{macros::format_description, UtcOffset};
+
+use crate::DateTime;
+
+use super::schedule::Event;
+
+/// Lines longer than this many bytes are folded, as required by RFC 5545.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Which events to export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Scope {
+    /// All starred events.
+    Favourites,
+    /// All events passing the current filter.
+    Filtered,
+}
+
+impl Scope {
+    /// What to call the file when exporting from within the viewer.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Favourites => "favourites.ics",
+            Self::Filtered => "filtered.ics",
+        }
+    }
+}
+
+/// Renders the given events as one iCalendar.
+pub fn ics<'a>(events: impl IntoIterator<Item = &'a Event>) -> String {
+    let mut calendar = Calendar::default();
+    let stamp = format_utc(DateTime::now_utc());
+
+    calendar.line("BEGIN", "VCALENDAR");
+    calendar.line("VERSION", "2.0");
+    calendar.line(
+        "PRODID",
+        concat!("-//inoe//inoe ", env!("CARGO_PKG_VERSION"), "//EN"),
+    );
+    calendar.line("CALSCALE", "GREGORIAN");
+
+    for event in events {
+        calendar.line("BEGIN", "VEVENT");
+        calendar.line("UID", &event.id.to_string());
+        calendar.line("DTSTAMP", &stamp);
+        calendar.line("DTSTART", &format_utc(event.start));
+        calendar.line("DTEND", &format_utc(event.end()));
+        calendar.line("SUMMARY", &escape(&event.title));
+        if !event.room.is_empty() {
+            calendar.line("LOCATION", &escape(&event.room));
+        }
+        if !event.url.is_empty() {
+            calendar.line("URL", &event.url);
+        }
+        if !event.r#abstract.is_empty() {
+            calendar.line("DESCRIPTION", &escape(&event.r#abstract));
+        }
+        calendar.line("END", "VEVENT");
+    }
+
+    calendar.line("END", "VCALENDAR");
+    calendar.content
+}
+
+#[derive(Default)]
+struct Calendar {
+    content: String,
+}
+
+impl Calendar {
+    /// Appends one content line, folding it if it's too long.
+    fn line(&mut self, name: &str, value: &str) {
+        let mut length = 0;
+        for ch in name.chars().chain([':']).chain(value.chars()) {
+            // continuation lines start with a space, which counts towards the length, too
+            if length + ch.len_utf8() > MAX_LINE_LENGTH {
+                self.content.push_str("\r\n ");
+                length = 1;
+            }
+            self.content.push(ch);
+            length += ch.len_utf8();
+        }
+        self.content.push_str("\r\n");
+    }
+}
+
+/// Formats the point in time as UTC, e.g. `20231227T103000Z`.
+fn format_utc(point: DateTime) -> String {
+    point
+        .to_offset(UtcOffset::UTC)
+        .format(format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .expect("formatting a date with a static description to succeed")
+}
+
+/// Escapes characters with a special meaning in iCalendar text values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.trim().chars() {
+        match ch {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture::{self, event};
+
+    /// The value of every line with the given name, as long as it fits into one line.
+    fn values<'a>(calendar: &'a str, name: &str) -> Vec<&'a str> {
+        calendar
+            .split("\r\n")
+            .filter_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .collect()
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(escape(" a, b; c\\d\r\nnext \n"), r"a\, b\; c\\d\nnext");
+    }
+
+    #[test]
+    fn long_lines_are_folded_between_characters() {
+        let value = "ä€x".repeat(30);
+        let mut calendar = Calendar::default();
+        calendar.line("DESCRIPTION", &value);
+
+        let lines: Vec<_> = calendar.content.trim_end().split("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+
+        let unfolded = calendar.content.replace("\r\n ", "");
+        assert_eq!(unfolded, format!("DESCRIPTION:{value}\r\n"));
+    }
+
+    #[test]
+    fn events_keep_their_guid_as_uid() {
+        let schedule = fixture::schedule();
+        let events = [&schedule[&event(1)], &schedule[&event(2)]];
+
+        let first = ics(events);
+        let second = ics(events);
+        assert_eq!(values(&first, "UID"), values(&second, "UID"));
+        assert_eq!(
+            values(&first, "UID"),
+            [event(1).to_string(), event(2).to_string()]
+        );
+        assert_eq!(
+            values(&first, "DTSTART"),
+            ["20231227T090000Z", "20231227T100000Z"]
+        );
+        assert_eq!(values(&first, "SUMMARY"), ["Opening", "Lockpicking"]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Events the user starred, persisted across launches.
+//!
+//! The on-disk format is deliberately boring: One event guid per line, so it can also be edited or
+//! shared by hand.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+
+use super::schedule::EventId;
+
+#[derive(Clone, Debug)]
+pub struct Favourites {
+    /// [`None`] if there's nowhere to persist them, then they only last until quitting.
+    path: Option<PathBuf>,
+    ids: BTreeSet<EventId>,
+    /// If the set was modified since the last time it was written to disk.
+    dirty: bool,
+}
+
+impl Favourites {
+    /// Reads the favourites from the given file. A missing file is not an error, but just means
+    /// that nothing was starred yet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()))
+            }
+        };
+
+        let ids = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.parse()
+                    .with_context(|| format!("invalid event guid in favourites: {line}"))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            path: Some(path),
+            ids,
+            dirty: false,
+        })
+    }
+
+    /// Starts out with nothing starred and never writes anything to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            ids: BTreeSet::new(),
+            dirty: false,
+        }
+    }
+
+    /// Where the favourites are stored if not overridden by the user. [`None`] if the platform has
+    /// no data directory, or it couldn't be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = dirs::data_dir()?;
+        Some(dir.join("inoe").join("favourites"))
+    }
+
+    /// Pure getter.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn contains(&self, id: &EventId) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EventId> {
+        self.ids.iter()
+    }
+
+    /// Stars the event if it isn't yet, unstars it otherwise.
+    pub fn toggle(&mut self, id: EventId) {
+        if !self.ids.remove(&id) {
+            self.ids.insert(id);
+        }
+        self.dirty = true;
+    }
+
+    /// Writes the favourites to disk if they changed since the last write. Does nothing if they're
+    /// only kept in memory.
+    pub fn flush(&mut self) -> Result<()> {
+        let Some(path) = self.path.as_deref().filter(|_| self.dirty) else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("could not create favourites directory")?;
+        }
+
+        let content: String = self.ids.iter().map(|id| format!("{id}\n")).collect();
+        fs::write(path, content).with_context(|| format!("could not write {}", path.display()))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture::event;
+
+    /// Where one test keeps its favourites, in a directory that doesn't exist yet.
+    fn path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("inoe-favourites-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("inoe").join("favourites")
+    }
+
+    #[test]
+    fn missing_file_means_nothing_starred() {
+        let path = path("missing");
+        let mut favourites = Favourites::load(&path).unwrap();
+
+        assert_eq!(favourites.iter().count(), 0);
+        // nothing changed, so there's no need to create the file
+        favourites.flush().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn toggled_favourites_survive_a_round_trip() {
+        let path = path("round-trip");
+        let mut favourites = Favourites::load(&path).unwrap();
+        for id in [2, 1, 3, 1] {
+            favourites.toggle(event(id));
+        }
+        favourites.flush().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}\n{}\n", event(2), event(3))
+        );
+        let loaded = Favourites::load(&path).unwrap();
+        assert_eq!(
+            loaded.iter().copied().collect::<Vec<_>>(),
+            [event(2), event(3)]
+        );
+    }
+
+    #[test]
+    fn hand_edited_files_are_understood() {
+        let path = path("hand-edited");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        fs::write(&path, format!("\n  {}  \n\n{}", event(5), event(1))).unwrap();
+        let loaded = Favourites::load(&path).unwrap();
+        assert!(loaded.contains(&event(1)) && loaded.contains(&event(5)));
+
+        fs::write(&path, "not a guid\n").unwrap();
+        assert!(Favourites::load(&path).is_err());
+    }
+
+    #[test]
+    fn in_memory_favourites_are_never_written() {
+        let mut favourites = Favourites::in_memory();
+        favourites.toggle(event(1));
+
+        assert!(favourites.contains(&event(1)));
+        assert_eq!(favourites.path(), None);
+        favourites.flush().unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+//! Getting the schedule file from wherever the user pointed us to.
+//!
+//! Remote schedules are downloaded into a cache directory. On the next launch, the server is asked
+//! if anything changed using `If-None-Match` and `If-Modified-Since`, so the cached copy can be
+//! reused without downloading it again — or if the server is not reachable at all, e.g. because
+//! the congress Wi-Fi gave up.
+
+use std::{
+    fmt, fs,
+    io::{self, ErrorKind, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use eyre::{bail, Context, ContextCompat, Result};
+use uuid::Uuid;
+
+/// How long to wait for the server before falling back to the cache.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the schedule can be found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Path(PathBuf),
+    /// An `http://` or `https://` URL.
+    Url(String),
+}
+
+impl Source {
+    /// Returns a local path containing the schedule, downloading it into `cache_dir` if needed.
+    /// Only URLs need a cache directory.
+    pub fn resolve(&self, cache_dir: Option<&Path>) -> Result<PathBuf> {
+        match self {
+            Self::Path(path) => Ok(path.clone()),
+            Self::Url(url) => {
+                let cache_dir =
+                    cache_dir.context("could not find a cache directory to download into")?;
+                fetch(url, cache_dir)
+            }
+        }
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_url = ["http://", "https://"].into_iter().any(|scheme| {
+            s.get(..scheme.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+        });
+
+        Ok(match is_url {
+            true => Self::Url(s.to_string()),
+            false => Self::Path(s.into()),
+        })
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => path.display().fmt(f),
+            Self::Url(url) => url.fmt(f),
+        }
+    }
+}
+
+/// Where downloaded schedules are kept if not overridden, if the platform has such a place at all.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("inoe").join("schedules"))
+}
+
+/// Downloads the schedule at `url` into `cache_dir`, returning the path of the local copy.
+///
+/// If the server says the cached copy is still up to date, or can't be reached at all, the cached
+/// copy is used as-is. Only if there's no cached copy yet, failing to download is an error.
+pub fn fetch(url: &str, cache_dir: &Path) -> Result<PathBuf> {
+    let entry = CacheEntry::new(url, cache_dir);
+    let validators = entry.validators()?;
+    let is_cached = entry.content.exists();
+
+    let mut request = ureq::get(url).timeout(TIMEOUT);
+    if is_cached {
+        if let Some(etag) = &validators.etag {
+            request = request.set("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.set("If-Modified-Since", last_modified);
+        }
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        // offline or the server has a bad day, but we have something to show still
+        Err(_) if is_cached => return Ok(entry.content),
+        Err(err) => return Err(err).with_context(|| format!("could not download {url}")),
+    };
+
+    match response.status() {
+        304 if is_cached => return Ok(entry.content),
+        200 => (),
+        status => bail!("unexpected status {status} while downloading {url}"),
+    }
+
+    let validators = Validators {
+        etag: response.header("ETag").map(str::to_string),
+        last_modified: response.header("Last-Modified").map(str::to_string),
+    };
+
+    let mut content = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut content)
+        .with_context(|| format!("could not download {url}"))?;
+
+    fs::create_dir_all(cache_dir).context("could not create schedule cache directory")?;
+    // without validators the next request is unconditional, so a crash in between can't lead to
+    // new validators vouching for old content or the other way around
+    match fs::remove_file(&entry.validators) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            return Err(err).context("could not replace schedule cache metadata")
+        }
+        _ => (),
+    }
+    write_atomically(&entry.content, content).context("could not write schedule to cache")?;
+    write_atomically(&entry.validators, validators.to_string())
+        .context("could not write schedule cache metadata")?;
+
+    Ok(entry.content)
+}
+
+/// Writes the file under a temporary name first, so it's never seen half-written, e.g. after the
+/// disk ran full.
+fn write_atomically(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".part");
+
+    fs::write(&temporary, content)?;
+    fs::rename(&temporary, path)
+}
+
+/// Paths of one cached download.
+struct CacheEntry {
+    content: PathBuf,
+    /// Metadata for asking the server if the content is still up to date.
+    validators: PathBuf,
+}
+
+impl CacheEntry {
+    fn new(url: &str, cache_dir: &Path) -> Self {
+        // URLs can contain all sorts of things not allowed in paths, so let's just hash it
+        let key = Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes());
+
+        // keep the extension around so the format can still be detected by it
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let extension = ["json", "xml"]
+            .into_iter()
+            .find(|ext| path.ends_with(&format!(".{ext}")));
+        let content = match extension {
+            Some(extension) => format!("{key}.{extension}"),
+            None => key.to_string(),
+        };
+
+        Self {
+            content: cache_dir.join(content),
+            validators: cache_dir.join(format!("{key}.validators")),
+        }
+    }
+
+    fn validators(&self) -> Result<Validators> {
+        match fs::read_to_string(&self.validators) {
+            Ok(content) => Ok(content.parse().expect("parsing validators is infallible")),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Validators::default()),
+            Err(err) => Err(err).context("could not read schedule cache metadata"),
+        }
+    }
+}
+
+/// HTTP headers telling which version of the content was downloaded.
+#[derive(Debug, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl fmt::Display for Validators {
+    /// Uses a HTTP-header-like format, one header per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(etag) = &self.etag {
+            writeln!(f, "ETag: {etag}")?;
+        }
+        if let Some(last_modified) = &self.last_modified {
+            writeln!(f, "Last-Modified: {last_modified}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Validators {
+    type Err = std::convert::Infallible;
+
+    /// Unknown lines are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut validators = Self::default();
+
+        for (name, value) in s.lines().filter_map(|line| line.split_once(": ")) {
+            match name {
+                "ETag" => validators.etag = Some(value.to_string()),
+                "Last-Modified" => validators.last_modified = Some(value.to_string()),
+                _ => (),
+            }
+        }
+
+        Ok(validators)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use super::*;
+
+    /// A fresh directory for one test to keep its cache in.
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("inoe-fetch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Stands in for a HTTP server, answering one connection after another with the given raw
+    /// responses. Returns the URL to request and the head of each request received.
+    fn serve(responses: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/schedule.xml", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    let mut head = String::new();
+                    let mut reader = BufReader::new(&mut stream);
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim_end().is_empty() {
+                            break;
+                        }
+                        head.push_str(&line);
+                    }
+
+                    stream.write_all(response.as_bytes()).unwrap();
+                    head
+                })
+                .collect()
+        });
+
+        (url, server)
+    }
+
+    fn ok(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\n\
+            ETag: \"v1\"\r\n\
+            Last-Modified: Wed, 27 Dec 2023 10:00:00 GMT\r\n\
+            Content-Length: {}\r\n\
+            Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    const NOT_MODIFIED: &str = "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n";
+
+    #[test]
+    fn downloads_into_cache() {
+        let dir = cache_dir("download");
+        let (url, server) = serve(vec![ok("<schedule/>")]);
+
+        let path = fetch(&url, &dir).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<schedule/>");
+        assert_eq!(path.extension().unwrap(), "xml");
+        let validators = CacheEntry::new(&url, &dir).validators().unwrap();
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert!(validators.last_modified.is_some());
+        // nothing half-written is left lying around
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn reuses_cache_if_not_modified() {
+        let dir = cache_dir("not-modified");
+        let (url, server) = serve(vec![ok("<schedule/>"), NOT_MODIFIED.to_string()]);
+
+        fetch(&url, &dir).unwrap();
+        let path = fetch(&url, &dir).unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(fs::read_to_string(path).unwrap(), "<schedule/>");
+        assert!(!requests[0].contains("If-None-Match"));
+        assert!(requests[1].contains("If-None-Match: \"v1\""));
+        assert!(requests[1].contains("If-Modified-Since: Wed, 27 Dec 2023 10:00:00 GMT"));
+    }
+
+    #[test]
+    fn falls_back_to_cache_when_offline() {
+        let dir = cache_dir("offline");
+        let (url, server) = serve(vec![ok("<schedule/>")]);
+
+        fetch(&url, &dir).unwrap();
+        // the listener is gone along with the server thread, so connecting is refused
+        server.join().unwrap();
+        let path = fetch(&url, &dir).unwrap();
+
+        assert_eq!(fs::read_to_string(path).unwrap(), "<schedule/>");
+    }
+
+    #[test]
+    fn fails_when_offline_without_cache() {
+        let dir = cache_dir("offline-uncached");
+        let (url, server) = serve(vec![]);
+        server.join().unwrap();
+
+        assert!(fetch(&url, &dir).is_err());
+    }
+}
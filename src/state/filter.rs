@@ -0,0 +1,234 @@
+//! Restricting which events are shown in the grid and reachable by moving the selection.
+//!
+//! Values of the same field are OR'ed, different fields are AND'ed. So `track=Security`,
+//! `track=Hardware` and `language=en` show all English events from either track.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+use super::schedule::{Event, Schedule};
+
+/// What property of an event to filter by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    Track,
+    Room,
+    Language,
+    Type,
+}
+
+impl Field {
+    pub const ALL: [Self; 4] = [Self::Track, Self::Room, Self::Language, Self::Type];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Track => "track",
+            Self::Room => "room",
+            Self::Language => "language",
+            Self::Type => "type",
+        }
+    }
+
+    /// Returns the value of this field for the given event.
+    pub fn of(self, event: &Event) -> &str {
+        match self {
+            Self::Track => &event.track,
+            Self::Room => &event.room,
+            Self::Language => &event.language,
+            Self::Type => &event.r#type,
+        }
+    }
+
+    /// Returns all non-empty values this field takes in the given schedule, sorted and
+    /// deduplicated.
+    pub fn values(self, schedule: &Schedule) -> BTreeSet<&str> {
+        schedule
+            .events()
+            .map(|event| self.of(event))
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let known = Self::ALL.map(Self::name).join(", ");
+                format!("unknown filter field `{s}`, expected one of {known}")
+            })
+    }
+}
+
+/// One allowed value for one field, as in `track=Security`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Criterion {
+    pub field: Field,
+    pub value: String,
+}
+
+impl FromStr for Criterion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `field=value`, got `{s}`"))?;
+
+        Ok(Self {
+            field: field.trim().parse()?,
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    allowed: BTreeMap<Field, BTreeSet<String>>,
+}
+
+impl Filter {
+    /// Returns if the event should be visible. An empty filter lets everything through.
+    pub fn matches(&self, event: &Event) -> bool {
+        self.allowed
+            .iter()
+            .all(|(field, values)| values.contains(field.of(event)))
+    }
+
+    pub fn contains(&self, field: Field, value: &str) -> bool {
+        self.allowed
+            .get(&field)
+            .is_some_and(|values| values.contains(value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty()
+    }
+
+    /// Iterates over all allowed values, grouped by field.
+    pub fn iter(&self) -> impl Iterator<Item = (Field, &str)> {
+        self.allowed
+            .iter()
+            .flat_map(|(field, values)| values.iter().map(|value| (*field, value.as_str())))
+    }
+
+    pub fn insert(&mut self, criterion: Criterion) {
+        self.allowed
+            .entry(criterion.field)
+            .or_default()
+            .insert(criterion.value);
+    }
+
+    /// Allows the value if it isn't allowed already, removes it otherwise.
+    pub fn toggle(&mut self, criterion: Criterion) {
+        let values = self.allowed.entry(criterion.field).or_default();
+        if !values.remove(&criterion.value) {
+            values.insert(criterion.value);
+        }
+
+        // an empty set would hide everything, but it should rather mean "don't care"
+        if values.is_empty() {
+            self.allowed.remove(&criterion.field);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.allowed.clear();
+    }
+}
+
+impl FromIterator<Criterion> for Filter {
+    fn from_iter<I: IntoIterator<Item = Criterion>>(iter: I) -> Self {
+        let mut filter = Self::default();
+        for criterion in iter {
+            filter.insert(criterion);
+        }
+        filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture::{self, event};
+
+    fn criterion(text: &str) -> Criterion {
+        text.parse().unwrap()
+    }
+
+    /// Which events of the fixture pass the filter, by their `id` attribute.
+    fn passing(filter: &Filter) -> Vec<u8> {
+        let schedule = fixture::schedule();
+        (1..=6)
+            .filter(|id| filter.matches(&schedule[&event(*id)]))
+            .collect()
+    }
+
+    #[test]
+    fn criteria_are_parsed() {
+        assert_eq!(
+            criterion(" Track = Art & Beauty"),
+            Criterion {
+                field: Field::Track,
+                value: "Art & Beauty".to_string(),
+            }
+        );
+        assert!("speaker=Jane Doe".parse::<Criterion>().is_err());
+        assert!("track".parse::<Criterion>().is_err());
+    }
+
+    #[test]
+    fn values_of_one_field_are_alternatives() {
+        let filter: Filter = ["track=Security", "track=Art & Beauty", "language=en"]
+            .into_iter()
+            .map(criterion)
+            .collect();
+
+        assert_eq!(passing(&filter), [1, 3, 4, 5, 6]);
+        assert_eq!(passing(&Filter::default()), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn toggling_the_last_value_lets_everything_through() {
+        let mut filter = Filter::default();
+
+        filter.toggle(criterion("room=Saal 2"));
+        assert_eq!(passing(&filter), [5]);
+        assert!(filter.contains(Field::Room, "Saal 2"));
+
+        filter.toggle(criterion("room=Saal 2"));
+        assert!(filter.is_empty());
+        assert_eq!(passing(&filter), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn values_are_sorted_and_unique() {
+        let schedule = fixture::schedule();
+
+        assert_eq!(
+            Field::Track.values(&schedule),
+            BTreeSet::from(["Art & Beauty", "Security"])
+        );
+        assert_eq!(
+            Field::Language.values(&schedule),
+            BTreeSet::from(["de", "en"])
+        );
+        assert_eq!(
+            Field::Type.values(&schedule),
+            BTreeSet::from(["lecture", "workshop"])
+        );
+    }
+}
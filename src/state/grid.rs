@@ -0,0 +1,423 @@
+//! Layouting all events into columns, so they can be displayed and navigated in a grid.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter,
+};
+
+use either::Either;
+
+use crate::DateTime;
+
+use super::{
+    filter::Filter,
+    schedule::{self, EventId},
+};
+
+/// How many columns the [`GridLayout::Packed`] layout has, unless configured otherwise.
+pub const DEFAULT_COLUMNS: usize = 7;
+
+/// How events are assigned to columns.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GridLayout {
+    /// Events are put into the first free column, keeping the grid compact. The same room might
+    /// end up in different columns over time though.
+    #[default]
+    Packed,
+    /// Each room gets its own column, like in the official Fahrplan. Rooms with overlapping events
+    /// get as many as needed to show all of them.
+    Rooms,
+}
+
+/// A fully "simulated" schedule, where each timeslot is assigned.
+///
+/// **Note:** The current implementation for this is horribly inefficient and has a runtime of
+/// _O_(_nm_), where _n_ is the count of events and _m_ the number of concurrently occuring events.
+/// I'm sure there's some better and smarter way to do this implicitly and still create a table,
+/// but this works for the moment.
+#[derive(Debug, Default)]
+pub struct ScheduleGrid {
+    /// Names of the columns, if they have any. Empty for [`GridLayout::Packed`].
+    pub headers: Vec<String>,
+    /// Each row has the same length, namely [`ScheduleGrid::width`].
+    pub timeline: BTreeMap<DateTime, Vec<Option<EventId>>>,
+    width: usize,
+}
+
+impl ScheduleGrid {
+    /// Lays out all events of the schedule which pass the filter. `columns` is only used by
+    /// [`GridLayout::Packed`], the other layouts decide on their own.
+    pub fn new(
+        base: &schedule::Schedule,
+        filter: &Filter,
+        layout: GridLayout,
+        columns: usize,
+    ) -> Self {
+        match layout {
+            GridLayout::Packed => Self::packed(base, filter, columns),
+            GridLayout::Rooms => Self::by_room(base, filter),
+        }
+    }
+
+    fn packed(base: &schedule::Schedule, filter: &Filter, columns: usize) -> Self {
+        let mut active_events = SlottedVec::new(columns);
+        let mut grid = Self {
+            width: columns,
+            ..Self::default()
+        };
+
+        for (now, just_starting) in base.time_map() {
+            // cull inactive events
+            active_events.retain(|(_, end)| &*end > now);
+
+            let mut just_starting = just_starting
+                .iter()
+                .map(|event| &base[event])
+                .filter(|event| filter.matches(event))
+                .peekable();
+            if just_starting.peek().is_none() {
+                // nothing visible changes here, so no need for an own row
+                continue;
+            }
+
+            // insert all new ones
+            active_events.extend(just_starting.map(|event| (event.id, event.end())));
+
+            // note down anything that's active now
+            grid.timeline.insert(
+                *now,
+                active_events
+                    .iter()
+                    .map(|slot| slot.map(|(event, _end)| event))
+                    .collect(),
+            );
+        }
+
+        grid
+    }
+
+    fn by_room(base: &schedule::Schedule, filter: &Filter) -> Self {
+        let visible = || base.events().filter(|event| filter.matches(event));
+
+        // rooms without any visible event would just be empty columns
+        let mut rooms: Vec<String> = base
+            .rooms()
+            .iter()
+            .map(|room| room.name.clone())
+            .filter(|name| visible().any(|event| event.room == *name))
+            .collect();
+        // events could theoretically refer to rooms not declared anywhere
+        for event in visible() {
+            if !rooms.contains(&event.room) {
+                rooms.push(event.room.clone());
+            }
+        }
+
+        let indices: HashMap<&str, usize> = rooms
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.as_str(), idx))
+            .collect();
+
+        // usually a room holds one event at a time, but schedules can still overlap events in the
+        // same room, which then get one more column each so none of them is hidden
+        let mut lanes: Vec<Vec<Option<(EventId, DateTime)>>> = vec![Vec::new(); rooms.len()];
+        let mut rows = Vec::new();
+
+        for (now, just_starting) in base.time_map() {
+            for slot in lanes.iter_mut().flatten() {
+                if matches!(slot, Some((_, end)) if *end <= *now) {
+                    *slot = None;
+                }
+            }
+
+            let mut changed = false;
+            let just_starting = just_starting
+                .iter()
+                .map(|event| &base[event])
+                .filter(|event| filter.matches(event));
+            for event in just_starting {
+                let room = &mut lanes[indices[event.room.as_str()]];
+                let entry = Some((event.id, event.end()));
+                match room.iter_mut().find(|slot| slot.is_none()) {
+                    Some(slot) => *slot = entry,
+                    None => room.push(entry),
+                }
+                changed = true;
+            }
+
+            if changed {
+                let row: Vec<Vec<_>> = lanes
+                    .iter()
+                    .map(|room| {
+                        room.iter()
+                            .map(|slot| slot.map(|(event, _end)| event))
+                            .collect()
+                    })
+                    .collect();
+                rows.push((*now, row));
+            }
+        }
+
+        // lanes are never removed, so by now each room has as many as it needed at most
+        let widths: Vec<usize> = lanes.iter().map(Vec::len).collect();
+        let headers = rooms
+            .into_iter()
+            .zip(&widths)
+            .flat_map(|(name, width)| vec![name; *width])
+            .collect::<Vec<_>>();
+        let timeline = rows
+            .into_iter()
+            .map(|(now, row)| {
+                let row = row
+                    .into_iter()
+                    .zip(&widths)
+                    .flat_map(|(mut room, width)| {
+                        room.resize(*width, None);
+                        room
+                    })
+                    .collect();
+                (now, row)
+            })
+            .collect();
+
+        Self {
+            width: headers.len(),
+            headers,
+            timeline,
+        }
+    }
+
+    /// How many columns each row has.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the column the given event is placed in at the given row, if it's visible there at
+    /// all.
+    pub fn column_of(&self, row: DateTime, id: EventId) -> Option<usize> {
+        self.timeline
+            .get(&row)?
+            .iter()
+            .position(|slot| *slot == Some(id))
+    }
+
+    /// Finds the event visually above (`up == true`) or below the event `id` in column `column`,
+    /// starting the search at `row`.
+    ///
+    /// Empty slots and further slots occupied by the same event are skipped. If the column holds
+    /// nothing anymore in that direction, the nearest column of the next row with a different
+    /// event is used instead.
+    pub fn vertical_neighbor(
+        &self,
+        row: DateTime,
+        column: usize,
+        id: EventId,
+        up: bool,
+    ) -> Option<EventId> {
+        let rows = || {
+            let rows = match up {
+                true => Either::Left(self.timeline.range(..row).rev()),
+                false => Either::Right(self.timeline.range(row..).skip(1)),
+            };
+            rows.map(|(_, slots)| slots)
+        };
+
+        // first try to stay in the same column
+        let same_column = rows()
+            .filter_map(|slots| slots[column])
+            .find(|candidate| *candidate != id);
+        if same_column.is_some() {
+            return same_column;
+        }
+
+        // seems like the column ran dry, so let's just take the closest one
+        rows().find_map(|slots| {
+            slots
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, slot)| Some((idx.abs_diff(column), (*slot)?)))
+                .filter(|(_, candidate)| *candidate != id)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, candidate)| candidate)
+        })
+    }
+
+    /// Finds the event closest to `row` in the next column to the left (`left == true`) or right
+    /// of `column` that has any event at all.
+    ///
+    /// Prefers the event running at `row`, then the next one starting after it, then the last one
+    /// before it.
+    pub fn horizontal_neighbor(&self, row: DateTime, column: usize, left: bool) -> Option<EventId> {
+        let columns = match left {
+            true => Either::Left((0..column).rev()),
+            false => Either::Right(column + 1..self.width),
+        };
+
+        columns.into_iter().find_map(|column| {
+            let at = |(_, slots): (_, &Vec<Option<EventId>>)| slots[column];
+            self.timeline
+                .range(..=row)
+                .next_back()
+                .and_then(at)
+                .or_else(|| self.timeline.range(row..).find_map(at))
+                .or_else(|| self.timeline.range(..row).rev().find_map(at))
+        })
+    }
+}
+
+/// [`Vec`], but fixed to a size given on creation and keeping elements at the same position
+/// regardless of elements removed in before.
+#[derive(Debug)]
+pub struct SlottedVec<T> {
+    data: Vec<Option<T>>,
+}
+
+impl<T> SlottedVec<T> {
+    /// Creates a vector with `len` empty slots.
+    pub fn new(len: usize) -> Self {
+        Self {
+            data: iter::repeat_with(|| None).take(len).collect(),
+        }
+    }
+
+    /// Runs through all contained elements and removes them if the predicate returns [`false`].
+    pub fn retain(&mut self, mut predicate: impl FnMut(&mut T) -> bool) {
+        for slot in &mut self.data {
+            let Some(item) = slot else { continue };
+
+            if !(predicate)(item) {
+                *slot = None;
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Option<T>> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Option<T>> {
+        self.data.iter_mut()
+    }
+}
+
+impl<T> Extend<T> for SlottedVec<T> {
+    /// Try to fill the vector up from the start.
+    ///
+    /// Once the vector is all filled up, the iterator is dropped, without pulling remaining
+    /// elements.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // a bit like k-merge
+        // we just iterate through both the vec and the iter
+        let mut iter = iter.into_iter();
+        let target = self.iter_mut();
+        for slot in target {
+            if slot.is_some() {
+                continue;
+            }
+
+            // oh noice, we have a free slot, let's fill it
+            let item = iter.next();
+            if item.is_none() {
+                // oh no the target iter is empty qwq
+                // technically we don't know if the iterator is fused
+                // so we could just continue here? idk
+                return;
+            }
+            *slot = item;
+        }
+    }
+}
+
+impl<T> FromIterator<Option<T>> for SlottedVec<T> {
+    /// Takes over all slots of the iterator, so the vector is as long as the iterator.
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        Self {
+            data: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::state::schedule::fixture::{self, event};
+
+    fn grid(layout: GridLayout, columns: usize) -> ScheduleGrid {
+        ScheduleGrid::new(&fixture::schedule(), &Filter::default(), layout, columns)
+    }
+
+    #[test]
+    fn packed_drops_what_does_not_fit() {
+        let grid = grid(GridLayout::Packed, 1);
+        let opening = datetime!(2023-12-27 10:00 +1);
+
+        assert_eq!(grid.width(), 1);
+        assert_eq!(grid.timeline[&opening], [Some(event(1))]);
+        assert_eq!(grid.column_of(opening, event(1)), Some(0));
+        assert_eq!(grid.column_of(opening, event(5)), None);
+    }
+
+    #[test]
+    fn vertical_neighbors_stop_at_the_edges() {
+        let grid = grid(GridLayout::Packed, 2);
+        let first = datetime!(2023-12-27 10:00 +1);
+        let last = datetime!(2023-12-28 12:00 +1);
+
+        assert_eq!(grid.vertical_neighbor(first, 0, event(1), true), None);
+        assert_eq!(grid.vertical_neighbor(last, 0, event(6), false), None);
+        assert_eq!(
+            grid.vertical_neighbor(first, 0, event(1), false),
+            Some(event(2))
+        );
+        // the second column runs dry after the clash, so the closest one takes over
+        assert_eq!(
+            grid.vertical_neighbor(datetime!(2023-12-27 11:30 +1), 1, event(3), false),
+            Some(event(4))
+        );
+    }
+
+    #[test]
+    fn rooms_get_a_column_per_overlapping_event() {
+        let grid = grid(GridLayout::Rooms, 0);
+        let row = |time| grid.timeline[&time].clone();
+
+        assert_eq!(grid.headers, ["Saal 1", "Saal 1", "Saal 2"]);
+        assert_eq!(
+            row(datetime!(2023-12-27 10:00 +1)),
+            [Some(event(1)), None, Some(event(5))]
+        );
+        assert_eq!(
+            row(datetime!(2023-12-27 11:30 +1)),
+            [Some(event(2)), Some(event(3)), None]
+        );
+        // once the clash is over, the first lane is used again
+        assert_eq!(
+            row(datetime!(2023-12-28 01:00 +1)),
+            [Some(event(4)), None, None]
+        );
+    }
+
+    #[test]
+    fn rooms_without_visible_events_are_left_out() {
+        let filter = Filter::from_iter(["room=Saal 2".parse().unwrap()]);
+        let grid = ScheduleGrid::new(&fixture::schedule(), &filter, GridLayout::Rooms, 0);
+
+        assert_eq!(grid.headers, ["Saal 2"]);
+        assert_eq!(grid.timeline.len(), 1);
+    }
+
+    #[test]
+    fn horizontal_neighbors_prefer_what_runs_at_the_row() {
+        let grid = grid(GridLayout::Rooms, 0);
+        let clash = datetime!(2023-12-27 11:30 +1);
+
+        assert_eq!(grid.horizontal_neighbor(clash, 0, false), Some(event(3)));
+        assert_eq!(grid.horizontal_neighbor(clash, 1, true), Some(event(2)));
+        // nothing runs or follows in the last room, so the last one before is taken
+        assert_eq!(grid.horizontal_neighbor(clash, 1, false), Some(event(5)));
+        assert_eq!(grid.horizontal_neighbor(clash, 2, false), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Opening URLs in whatever the system thinks is responsible for them, usually the browser.
+
+use std::{
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use eyre::{Context, Result};
+
+/// What hands URLs over to the browser on this platform.
+const DEFAULT_OPENER: &str = match cfg!(target_os = "macos") {
+    true => "open",
+    false => "xdg-open",
+};
+
+/// Command to open URLs with, the URL is appended as last argument.
+///
+/// Written like on a shell, e.g. `firefox --new-tab`, though without any quoting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opener {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Opener {
+    /// Starts opening the URL without waiting for it, since some browsers only return once closed.
+    pub fn open(&self, url: &str) -> Result<()> {
+        // the TUI owns the terminal, so the opener shouldn't write anything into it
+        Command::new(&self.program)
+            .args(&self.args)
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("could not run {}", self.program))?;
+
+        Ok(())
+    }
+}
+
+impl Default for Opener {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_OPENER.to_string(),
+            args: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Opener {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace().map(str::to_string);
+        let program = words
+            .next()
+            .ok_or_else(|| "opener needs at least a program to run".to_string())?;
+
+        Ok(Self {
+            program,
+            args: words.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openers_are_split_into_words() {
+        let opener: Opener = "firefox  --new-tab ".parse().unwrap();
+        assert_eq!(
+            opener,
+            Opener {
+                program: "firefox".to_string(),
+                args: vec!["--new-tab".to_string()],
+            }
+        );
+
+        assert!(" ".parse::<Opener>().is_err());
+    }
+}
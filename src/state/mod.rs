@@ -1,24 +1,65 @@
+pub mod diff;
+pub mod export;
+pub mod favourites;
+pub mod fetch;
+pub mod filter;
+pub mod grid;
+pub mod link;
 pub mod schedule;
+pub mod session;
 pub mod store;
+pub mod timezone;
+
+use std::sync::mpsc::{Receiver, TryRecvError};
 
 use eyre::Result;
 
 use crate::{config::Config, Action};
-use store::Store;
+use store::{Loaded, Store};
 
 pub struct Dispatcher {
     pub store: Store,
+    /// The reload running in the background if there is one, and if it's a quiet one.
+    reload: Option<(Receiver<Loaded>, bool)>,
 }
 
 impl Dispatcher {
     pub fn new(config: &Config) -> Result<Self> {
         Ok(Self {
             store: Store::new(config)?,
+            reload: None,
         })
     }
 
-    pub fn dispatch(&mut self, action: Action) {
+    /// Applies the action and persists anything that needs to survive a restart.
+    pub fn dispatch(&mut self, action: Action) -> Result<()> {
+        if let Action::Reload { quiet } = action {
+            self.reload = match self.reload.take() {
+                // already on its way, but the user might want to see the outcome now
+                Some((receiver, running_quiet)) => Some((receiver, running_quiet && quiet)),
+                None => Some((self.store.state().reload_in_background(), quiet)),
+            };
+        }
+
         self.store.update(action);
+        self.store.flush()
+    }
+
+    /// Returns the outcome of the background reload as action, once it's done.
+    pub fn poll(&mut self) -> Option<Action> {
+        let (receiver, quiet) = self.reload.as_ref()?;
+        let schedule = match receiver.try_recv() {
+            Ok(schedule) => schedule,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("reloading crashed".to_string()),
+        };
+
+        let action = Action::Reloaded {
+            quiet: *quiet,
+            schedule,
+        };
+        self.reload = None;
+        Some(action)
     }
 }
 
@@ -0,0 +1,476 @@
+//! Keeping the converted [`Schedule`] around in rkyv's binary format, since parsing the whole
+//! congress XML on every launch is noticeably slow on older laptops.
+//!
+//! The cache is keyed by the source location, and only used if the content of the source, the
+//! version of inoe that wrote it and [`CACHE_FORMAT`] all match. Otherwise the source is parsed as
+//! usual and the cache overwritten.
+//!
+//! The types here mirror the ones of [`super`], just in a form rkyv can handle. The time map
+//! isn't stored at all, it's rebuilt while converting back.
+//!
+//! Loading validates the archive and builds the [`super::Schedule`] right from it, without
+//! deserializing into the types here first.
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use rkyv::{
+    option::ArchivedOption, string::ArchivedString, vec::ArchivedVec, AlignedVec, Archive,
+    Serialize,
+};
+use time::{error::ComponentRange, Date, Duration, OffsetDateTime, Time, UtcOffset};
+use uuid::Uuid;
+
+use crate::DateTime;
+
+use super::convert::insert_event;
+
+const INOE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Has to be bumped whenever the types here change, so caches written by a build with the same
+/// version but another layout aren't read.
+const CACHE_FORMAT: u32 = 2;
+
+/// Loads the schedule at `path`, using the cached version in `cache_dir` if it's still up to date.
+/// Failing to read or write the cache, or not having one at all, is not an error, it's just slower
+/// then.
+pub fn load(path: &Path, cache_dir: Option<&Path>) -> Result<super::Schedule> {
+    let content = fs::read(path).context("could not open requested schedule")?;
+    let Some(cache_dir) = cache_dir else {
+        return super::Schedule::from_bytes(&content, path);
+    };
+    let content_hash = hash(&content);
+    let cache_path = cache_path(path, cache_dir);
+
+    if let Some(schedule) = read(&cache_path, content_hash) {
+        return Ok(schedule);
+    }
+
+    let schedule = super::Schedule::from_bytes(&content, path)?;
+    // a read-only cache dir or full disk is no reason to not show the schedule
+    let _ = write(&cache_path, content_hash, &schedule);
+
+    Ok(schedule)
+}
+
+/// Where the cache for the schedule at `path` is stored.
+fn cache_path(path: &Path, cache_dir: &Path) -> PathBuf {
+    // canonicalizing so launching from another directory still hits the same cache
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let key = Uuid::new_v5(&Uuid::NAMESPACE_OID, path.to_string_lossy().as_bytes());
+    cache_dir.join(format!("{key}.rkyv"))
+}
+
+/// Stable across platforms and Rust versions, unlike [`std::hash::DefaultHasher`].
+fn hash(content: &[u8]) -> [u8; 16] {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, content).into_bytes()
+}
+
+/// Returns the cached schedule if there is one, it's valid and matches the content hash.
+fn read(cache_path: &Path, content_hash: [u8; 16]) -> Option<super::Schedule> {
+    // rkyv needs the bytes to be aligned, so they're read right into an aligned buffer
+    let mut bytes = AlignedVec::new();
+    bytes
+        .extend_from_reader(&mut File::open(cache_path).ok()?)
+        .ok()?;
+
+    // checked, so a truncated or otherwise broken cache is just ignored
+    let archived = rkyv::check_archived_root::<Entry>(&bytes).ok()?;
+    if archived.cache_format != CACHE_FORMAT
+        || archived.inoe_version != INOE_VERSION
+        || archived.content_hash != content_hash
+    {
+        return None;
+    }
+
+    (&archived.schedule).try_into().ok()
+}
+
+fn write(cache_path: &Path, content_hash: [u8; 16], schedule: &super::Schedule) -> Result<()> {
+    let entry = Entry {
+        cache_format: CACHE_FORMAT,
+        inoe_version: INOE_VERSION.to_string(),
+        content_hash,
+        schedule: schedule.into(),
+    };
+    let bytes = rkyv::to_bytes::<_, 4096>(&entry).context("schedule serialization failure")?;
+
+    if let Some(dir) = cache_path.parent() {
+        fs::create_dir_all(dir).context("could not create schedule cache directory")?;
+    }
+    fs::write(cache_path, bytes).context("could not write parsed schedule to cache")
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Entry {
+    cache_format: u32,
+    inoe_version: String,
+    content_hash: [u8; 16],
+    schedule: Schedule,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Schedule {
+    conference: Conference,
+    version: Option<String>,
+    rooms: Vec<Room>,
+    days: Vec<Day>,
+    events: Vec<Event>,
+    persons: Vec<Person>,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Conference {
+    acronym: String,
+    title: String,
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
+    url: Option<String>,
+    /// Seconds since midnight.
+    day_change: Option<u32>,
+    tracks: Vec<Track>,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Track {
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Room {
+    id: [u8; 16],
+    name: String,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Day {
+    index: u32,
+    /// Julian day number.
+    date: i32,
+    start: Timestamp,
+    end: Timestamp,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Event {
+    id: [u8; 16],
+    number: Option<u64>,
+    slug: String,
+    start: Timestamp,
+    /// In seconds.
+    duration: i64,
+    title: String,
+    subtitle: String,
+    r#abstract: String,
+    description: String,
+    room: String,
+    track: String,
+    r#type: String,
+    language: String,
+    url: String,
+    feedback_url: Option<String>,
+    links: Vec<(String, String)>,
+    attachments: Vec<(String, String)>,
+    logo: Option<String>,
+    recorded: Option<bool>,
+    recording_license: Option<String>,
+    persons: Vec<[u8; 16]>,
+}
+
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Person {
+    id: [u8; 16],
+    number: Option<u64>,
+    code: Option<String>,
+    name: String,
+}
+
+/// A point in time together with the offset it was given in.
+#[derive(Archive, Serialize)]
+#[archive(check_bytes)]
+struct Timestamp {
+    unix: i64,
+    /// In seconds.
+    offset: i32,
+}
+
+impl From<&super::Schedule> for Schedule {
+    fn from(schedule: &super::Schedule) -> Self {
+        let conference = &schedule.conference;
+
+        Self {
+            conference: Conference {
+                acronym: conference.acronym.clone(),
+                title: conference.title.clone(),
+                start: conference.start.map(Timestamp::from),
+                end: conference.end.map(Timestamp::from),
+                url: conference.url.clone(),
+                day_change: conference
+                    .day_change
+                    .map(|time| (time - Time::MIDNIGHT).whole_seconds() as u32),
+                tracks: conference
+                    .tracks
+                    .iter()
+                    .map(|track| Track {
+                        name: track.name.clone(),
+                        color: track.color.clone(),
+                    })
+                    .collect(),
+            },
+            version: schedule.version.clone(),
+            rooms: schedule
+                .rooms
+                .iter()
+                .map(|room| Room {
+                    id: room.id.0.into_bytes(),
+                    name: room.name.clone(),
+                })
+                .collect(),
+            days: schedule
+                .days
+                .iter()
+                .map(|day| Day {
+                    index: day.index,
+                    date: day.date.to_julian_day(),
+                    start: day.start.into(),
+                    end: day.end.into(),
+                })
+                .collect(),
+            events: schedule
+                .coords()
+                .map(|(_, event)| Event {
+                    id: event.id.0.into_bytes(),
+                    number: event.number,
+                    slug: event.slug.clone(),
+                    start: event.start.into(),
+                    duration: event.duration.whole_seconds(),
+                    title: event.title.clone(),
+                    subtitle: event.subtitle.clone(),
+                    r#abstract: event.r#abstract.clone(),
+                    description: event.description.clone(),
+                    room: event.room.clone(),
+                    track: event.track.clone(),
+                    r#type: event.r#type.clone(),
+                    language: event.language.clone(),
+                    url: event.url.clone(),
+                    feedback_url: event.feedback_url.clone(),
+                    links: event.links.clone().into_iter().collect(),
+                    attachments: event.attachments.clone().into_iter().collect(),
+                    logo: event.logo.clone(),
+                    recorded: event.recording.recorded,
+                    recording_license: event.recording.license.clone(),
+                    persons: event.persons.iter().map(|id| id.0.into_bytes()).collect(),
+                })
+                .collect(),
+            persons: schedule
+                .persons
+                .values()
+                .map(|person| Person {
+                    id: person.id.0.into_bytes(),
+                    number: person.number,
+                    code: person.code.clone(),
+                    name: person.name.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&ArchivedSchedule> for super::Schedule {
+    type Error = ComponentRange;
+
+    fn try_from(cached: &ArchivedSchedule) -> Result<Self, Self::Error> {
+        let conference = &cached.conference;
+        let string =
+            |text: &ArchivedOption<ArchivedString>| text.as_ref().map(|text| text.to_string());
+
+        let mut schedule = Self {
+            conference: super::Conference {
+                acronym: conference.acronym.to_string(),
+                title: conference.title.to_string(),
+                start: conference
+                    .start
+                    .as_ref()
+                    .map(DateTime::try_from)
+                    .transpose()?,
+                end: conference
+                    .end
+                    .as_ref()
+                    .map(DateTime::try_from)
+                    .transpose()?,
+                url: string(&conference.url),
+                day_change: conference
+                    .day_change
+                    .as_ref()
+                    .map(|seconds| Time::MIDNIGHT + Duration::seconds((*seconds).into())),
+                tracks: conference
+                    .tracks
+                    .iter()
+                    .map(|track| super::Track {
+                        name: track.name.to_string(),
+                        color: string(&track.color),
+                    })
+                    .collect(),
+            },
+            version: string(&cached.version),
+            rooms: cached
+                .rooms
+                .iter()
+                .map(|room| super::Room {
+                    id: super::RoomId(Uuid::from_bytes(room.id)),
+                    name: room.name.to_string(),
+                })
+                .collect(),
+            days: cached
+                .days
+                .iter()
+                .map(|day| {
+                    Ok(super::Day {
+                        index: day.index,
+                        date: Date::from_julian_day(day.date)?,
+                        start: (&day.start).try_into()?,
+                        end: (&day.end).try_into()?,
+                    })
+                })
+                .collect::<Result<_, Self::Error>>()?,
+            ..Self::default()
+        };
+
+        schedule.persons = cached
+            .persons
+            .iter()
+            .map(|person| {
+                let id = super::PersonId(Uuid::from_bytes(person.id));
+                let person = super::Person {
+                    id,
+                    number: person.number.as_ref().copied(),
+                    code: string(&person.code),
+                    name: person.name.to_string(),
+                };
+                (id, person)
+            })
+            .collect();
+
+        let pairs = |pairs: &ArchivedVec<(ArchivedString, ArchivedString)>| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        for event in cached.events.iter() {
+            let event = super::Event {
+                id: super::EventId(Uuid::from_bytes(event.id)),
+                number: event.number.as_ref().copied(),
+                slug: event.slug.to_string(),
+                start: (&event.start).try_into()?,
+                duration: Duration::seconds(event.duration),
+                title: event.title.to_string(),
+                subtitle: event.subtitle.to_string(),
+                r#abstract: event.r#abstract.to_string(),
+                description: event.description.to_string(),
+                room: event.room.to_string(),
+                track: event.track.to_string(),
+                r#type: event.r#type.to_string(),
+                language: event.language.to_string(),
+                url: event.url.to_string(),
+                feedback_url: string(&event.feedback_url),
+                links: pairs(&event.links),
+                attachments: pairs(&event.attachments),
+                logo: string(&event.logo),
+                recording: super::Recording {
+                    recorded: event.recorded.as_ref().copied(),
+                    license: string(&event.recording_license),
+                },
+                persons: event
+                    .persons
+                    .iter()
+                    .map(|id| super::PersonId(Uuid::from_bytes(*id)))
+                    .collect(),
+            };
+            // persons are already in place
+            insert_event(&mut schedule, event, Vec::new());
+        }
+
+        Ok(schedule)
+    }
+}
+
+impl From<DateTime> for Timestamp {
+    fn from(point: DateTime) -> Self {
+        Self {
+            unix: point.unix_timestamp(),
+            offset: point.offset().whole_seconds(),
+        }
+    }
+}
+
+impl TryFrom<&ArchivedTimestamp> for DateTime {
+    type Error = ComponentRange;
+
+    fn try_from(timestamp: &ArchivedTimestamp) -> Result<Self, Self::Error> {
+        let offset = UtcOffset::from_whole_seconds(timestamp.offset)?;
+        Ok(OffsetDateTime::from_unix_timestamp(timestamp.unix)?.to_offset(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture;
+
+    fn cache_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("inoe-cache-{name}-{}", std::process::id()))
+            .join("cache.rkyv")
+    }
+
+    #[test]
+    fn schedule_survives_the_round_trip() {
+        let schedule = fixture::schedule();
+        let path = cache_path("round-trip");
+        write(&path, [1; 16], &schedule).unwrap();
+        let cached = read(&path, [1; 16]).unwrap();
+
+        // the order of events starting at the same time is what the selection relies on
+        assert_eq!(cached.time_map, schedule.time_map);
+        assert_eq!(cached.person_events, schedule.person_events);
+        assert_eq!(cached.days, schedule.days);
+        assert_eq!(cached.events, schedule.events);
+        assert_eq!(cached.persons, schedule.persons);
+        assert_eq!(cached.rooms, schedule.rooms);
+        assert_eq!(cached.conference, schedule.conference);
+        assert_eq!(cached.version, schedule.version);
+    }
+
+    #[test]
+    fn stale_caches_are_ignored() {
+        let schedule = fixture::schedule();
+        let path = cache_path("stale");
+        write(&path, [1; 16], &schedule).unwrap();
+        assert!(read(&path, [2; 16]).is_none());
+
+        let entry = Entry {
+            cache_format: CACHE_FORMAT - 1,
+            inoe_version: INOE_VERSION.to_string(),
+            content_hash: [1; 16],
+            schedule: (&schedule).into(),
+        };
+        fs::write(&path, rkyv::to_bytes::<_, 4096>(&entry).unwrap()).unwrap();
+        assert!(read(&path, [1; 16]).is_none());
+
+        fs::write(&path, "not an archive").unwrap();
+        assert!(read(&path, [1; 16]).is_none());
+    }
+}
@@ -1,37 +1,85 @@
+//! Turning the XML model into a [`super::Schedule`], plus the helpers the JSON conversion in
+//! [`super::convert_json`] shares.
+
 use time::{Duration, Time};
+use uuid::Uuid;
 
 use super::model;
 
 impl From<model::Schedule> for super::Schedule {
     /// Walks through the entire schedule and formats it in such a way that it becomes usable.
     fn from(model: model::Schedule) -> Self {
-        let mut schedule = Self::default();
+        let base_url = model.conference.url.clone();
+        let mut schedule = Self {
+            conference: realize_conference(model.conference),
+            version: model.version,
+            days: model
+                .days
+                .iter()
+                .map(|day| super::Day {
+                    index: day.index,
+                    date: day.date,
+                    start: day.start,
+                    end: day.end,
+                })
+                .collect(),
+            ..Self::default()
+        };
+        schedule.days.sort_by_key(|day| day.start);
 
-        let events = model
-            .days
-            .into_iter()
-            .flat_map(|day| day.rooms)
-            .flat_map(|room| room.events);
-        for event in events {
-            let (event, persons) = realize_event(event);
+        let rooms = model.days.into_iter().flat_map(|day| day.rooms);
+        let mut events = Vec::new();
+        for room in rooms {
+            let id = super::RoomId(room.guid);
+            if !schedule.rooms.iter().any(|known| known.id == id) {
+                schedule.rooms.push(super::Room {
+                    id,
+                    name: room.name,
+                });
+            }
 
-            schedule
-                .time_map
-                .entry(event.start)
-                .or_default()
-                .push(event.id);
-            schedule.events.insert(event.id, event);
+            events.extend(room.events);
+        }
 
-            schedule
-                .persons
-                .extend(persons.into_iter().map(|person| (person.id, person)));
+        for event in events {
+            let (event, persons) = realize_event(event, &base_url);
+            insert_event(&mut schedule, event, persons);
         }
 
         schedule
     }
 }
 
-fn realize_event(model: model::Event) -> (super::Event, Vec<super::Person>) {
+/// Puts the event into all lookup tables of the schedule.
+pub(super) fn insert_event(
+    schedule: &mut super::Schedule,
+    event: super::Event,
+    persons: Vec<super::Person>,
+) {
+    schedule
+        .time_map
+        .entry(event.start)
+        .or_default()
+        .push(event.id);
+
+    // the time map keeps events starting at the same time in insertion order, so the same here
+    for person in &event.persons {
+        let ids = schedule.person_events.entry(*person).or_default();
+        if ids.contains(&event.id) {
+            continue;
+        }
+        let idx = ids.partition_point(|id| schedule.events[id].start <= event.start);
+        ids.insert(idx, event.id);
+    }
+
+    schedule.events.insert(event.id, event);
+
+    schedule
+        .persons
+        .extend(persons.into_iter().map(|person| (person.id, person)));
+}
+
+fn realize_event(model: model::Event, base_url: &str) -> (super::Event, Vec<super::Person>) {
     let (person_ids, persons): (Vec<_>, Vec<_>) = model
         .persons
         .persons
@@ -42,6 +90,8 @@ fn realize_event(model: model::Event) -> (super::Event, Vec<super::Person>) {
 
     let event = super::Event {
         id: super::EventId(model.guid),
+        number: model.id,
+        slug: model.slug.unwrap_or_default(),
         start: model.date,
         duration: time_to_duration(model.duration),
         title: model.title,
@@ -60,20 +110,255 @@ fn realize_event(model: model::Event) -> (super::Event, Vec<super::Person>) {
             .into_iter()
             .map(|link| (link.display, link.href))
             .collect(),
+        attachments: model
+            .attachments
+            .attachments
+            .into_iter()
+            .map(|attachment| (attachment.display, absolute(&attachment.href, base_url)))
+            .collect(),
+        logo: model
+            .logo
+            .filter(|logo| !logo.is_empty())
+            .map(|logo| absolute(&logo, base_url)),
+        recording: realize_recording(model.recording, model.do_not_record),
         persons: person_ids,
     };
 
     (event, persons)
 }
 
-fn time_to_duration(time: Time) -> Duration {
+fn realize_conference(model: model::Conference) -> super::Conference {
+    super::Conference {
+        acronym: model.acronym,
+        title: model.title,
+        start: Some(model.start),
+        end: Some(model.end),
+        url: Some(model.url),
+        day_change: model.day_change,
+        tracks: model
+            .tracks
+            .into_iter()
+            .map(|track| super::Track {
+                name: track.name,
+                color: Some(track.color),
+            })
+            .collect(),
+    }
+}
+
+pub(super) fn time_to_duration(time: Time) -> Duration {
     let (hours, minutes, seconds) = time.as_hms();
     Duration::seconds(i64::from(hours) * 60 * 60 + i64::from(minutes) * 60 + i64::from(seconds))
 }
 
+/// The newer `optout` takes precedence over `do_not_record` if both are given.
+fn realize_recording(
+    model: Option<model::Recording>,
+    do_not_record: Option<String>,
+) -> super::Recording {
+    let flag = |text: Option<String>| match text?.trim() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    };
+
+    let (license, optout) = match model {
+        Some(recording) => (recording.license, recording.optout),
+        None => (None, None),
+    };
+    let optout = flag(optout).or_else(|| flag(do_not_record));
+
+    super::Recording {
+        recorded: optout.map(|optout| !optout),
+        license: license.filter(|license| !license.trim().is_empty()),
+    }
+}
+
 fn realize_person(model: model::Person) -> super::Person {
+    // pretalx only gives out a short code, keyed the same way as for JSON
+    let id = model.guid.unwrap_or_else(|| {
+        let key = match (&model.code, model.id) {
+            (Some(code), _) => code.clone(),
+            (None, Some(id)) => id.to_string(),
+            (None, None) => model.name.clone(),
+        };
+        derive_guid(&key)
+    });
+
     super::Person {
-        id: super::PersonId(model.guid),
+        id: super::PersonId(id),
+        number: model.id,
+        code: model.code,
         name: model.name,
     }
 }
+
+/// Stand-in for entities that don't come with a guid. Stable across runs, so that it can be
+/// persisted just like a real one.
+pub(super) fn derive_guid(key: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+}
+
+/// Resolves URLs relative to the host of `base`, which frab uses for attachments and logos.
+pub(super) fn absolute(url: &str, base: &str) -> String {
+    let Some(scheme_end) = base.find("://") else {
+        // nothing to resolve against
+        return url.to_string();
+    };
+
+    if url.starts_with("//") {
+        return format!("{}:{url}", &base[..scheme_end]);
+    }
+    if !url.starts_with('/') {
+        return url.to_string();
+    }
+
+    let host_start = scheme_end + "://".len();
+    let host_end = base[host_start..]
+        .find('/')
+        .map_or(base.len(), |idx| host_start + idx);
+    format!("{}{url}", &base[..host_end])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use time::macros::{datetime, time};
+
+    use super::{absolute, derive_guid};
+    use crate::state::schedule::{
+        fixture::{self, event},
+        Conference, Person, PersonId, Recording, Track,
+    };
+
+    #[test]
+    fn conference_metadata_is_kept() {
+        let schedule = fixture::schedule();
+        let track = |name: &str, color: &str| Track {
+            name: name.to_string(),
+            color: Some(color.to_string()),
+        };
+
+        assert_eq!(
+            schedule.conference(),
+            &Conference {
+                acronym: "tc23".to_string(),
+                title: "Test Conference".to_string(),
+                start: Some(datetime!(2023-12-27 10:00 +1)),
+                end: Some(datetime!(2023-12-28 13:00 +1)),
+                url: Some("https://example.org/tc23/".to_string()),
+                day_change: Some(time!(04:00)),
+                tracks: vec![
+                    track("Security", "#18386b"),
+                    track("Art & Beauty", "#f9b000")
+                ],
+            }
+        );
+        assert_eq!(schedule.version(), Some("1.2"));
+        assert_eq!(
+            schedule.track("Art & Beauty"),
+            Some(&schedule.conference().tracks[1])
+        );
+        assert_eq!(schedule.track("Crypto"), None);
+    }
+
+    #[test]
+    fn recording_optout_wins_over_do_not_record() {
+        let schedule = fixture::schedule();
+        let recording = |id| schedule[&event(id)].recording.clone();
+
+        assert_eq!(
+            recording(1),
+            Recording {
+                recorded: Some(true),
+                license: Some("CC BY 4.0".to_string()),
+            }
+        );
+        // an empty license is as good as none
+        assert_eq!(
+            recording(2),
+            Recording {
+                recorded: Some(false),
+                license: None,
+            }
+        );
+        assert_eq!(recording(3).recorded, Some(false));
+        assert_eq!(recording(5).recorded, Some(true));
+        assert_eq!(recording(6), Recording::default());
+    }
+
+    #[test]
+    fn attachments_are_resolved_against_the_conference() {
+        let schedule = fixture::schedule();
+
+        assert_eq!(
+            schedule[&event(2)].attachments,
+            BTreeMap::from([
+                (
+                    "Paper".to_string(),
+                    "https://cdn.example.net/paper.pdf".to_string()
+                ),
+                (
+                    "Slides".to_string(),
+                    "https://example.org/media/tc23/slides.pdf".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn urls_are_made_absolute() {
+        let base = "https://example.org/tc23/";
+
+        assert_eq!(absolute("/logo.png", base), "https://example.org/logo.png");
+        assert_eq!(
+            absolute("//cdn.example.net/logo.png", base),
+            "https://cdn.example.net/logo.png"
+        );
+        assert_eq!(
+            absolute("http://example.net/logo.png", base),
+            "http://example.net/logo.png"
+        );
+        assert_eq!(
+            absolute("/logo.png", "https://example.org"),
+            "https://example.org/logo.png"
+        );
+        // nothing to resolve against
+        assert_eq!(absolute("/logo.png", "example.org"), "/logo.png");
+    }
+
+    #[test]
+    fn persons_without_guid_get_a_stable_one() {
+        let schedule = fixture::schedule();
+        let persons = |id| -> Vec<Person> {
+            let event = &schedule[&event(id)];
+            event
+                .persons
+                .iter()
+                .map(|id| schedule[id].clone())
+                .collect()
+        };
+
+        let jane = Person {
+            id: PersonId("00000000-0000-4000-8000-0000000000a1".parse().unwrap()),
+            number: Some(11),
+            code: None,
+            name: "Jane Doe".to_string(),
+        };
+        let john = Person {
+            id: PersonId(derive_guid("XYZ12")),
+            number: None,
+            code: Some("XYZ12".to_string()),
+            name: "John Smith".to_string(),
+        };
+        let jo = Person {
+            id: PersonId(derive_guid("13")),
+            number: Some(13),
+            code: None,
+            name: "Jo Hannes".to_string(),
+        };
+        assert_eq!(persons(2), [john, jane]);
+        assert_eq!(persons(3), [jo]);
+    }
+}
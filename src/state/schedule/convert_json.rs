@@ -0,0 +1,192 @@
+//! Turning the frab/pretalx JSON model into a [`super::Schedule`], the same way [`super::convert`]
+//! does for XML. Everything both formats share is reused from there.
+
+use time::{format_description::well_known::Rfc3339, Duration, Time};
+
+use crate::DateTime;
+
+use super::{
+    convert::{absolute, derive_guid, insert_event, time_to_duration},
+    model_json,
+};
+
+impl From<model_json::Schedule> for super::Schedule {
+    /// Same as the [`From`] impl for the XML model, just for JSON.
+    fn from(model: model_json::Schedule) -> Self {
+        let model_json::Conference {
+            acronym,
+            title,
+            start,
+            end,
+            url,
+            day_change,
+            tracks,
+            rooms,
+            days,
+        } = model.conference;
+
+        let parse_time =
+            |time: Option<String>| time.and_then(|time| DateTime::parse(&time, &Rfc3339).ok());
+        let conference = super::Conference {
+            acronym,
+            title,
+            start: parse_time(start),
+            end: parse_time(end),
+            url,
+            day_change,
+            tracks: tracks
+                .into_iter()
+                .map(|track| super::Track {
+                    name: track.name,
+                    color: track.color,
+                })
+                .collect(),
+        };
+
+        let mut schedule = Self {
+            conference,
+            version: model.version,
+            rooms: rooms
+                .into_iter()
+                .map(|room| super::Room {
+                    id: super::RoomId(room.guid.unwrap_or_else(|| derive_guid(&room.name))),
+                    name: room.name,
+                })
+                .collect(),
+            ..Self::default()
+        };
+
+        let mut day_bounds = Vec::new();
+        let mut events = Vec::new();
+        for day in days {
+            day_bounds.push((day.index, day.date, day.day_start, day.day_end));
+            events.extend(day.rooms.into_values().flatten());
+        }
+
+        let base_url = schedule.conference.url.clone().unwrap_or_default();
+        for event in events {
+            let (event, persons) = realize_event(event, &base_url);
+            insert_event(&mut schedule, event, persons);
+        }
+
+        // if the bounds are missing, the day change is the next best thing to go by
+        let day_change = schedule.conference.day_change.unwrap_or(Time::MIDNIGHT);
+        schedule.days = day_bounds
+            .into_iter()
+            .map(|(index, date, start, end)| {
+                let start = start.unwrap_or_else(|| {
+                    let start = date.with_time(day_change);
+                    start.assume_offset(schedule.offset_at(start.assume_utc()))
+                });
+                super::Day {
+                    index,
+                    date,
+                    start,
+                    end: end.unwrap_or(start + Duration::DAY),
+                }
+            })
+            .collect();
+        schedule.days.sort_by_key(|day| day.start);
+
+        schedule
+    }
+}
+
+fn realize_event(model: model_json::Event, base_url: &str) -> (super::Event, Vec<super::Person>) {
+    let (person_ids, persons): (Vec<_>, Vec<_>) = model
+        .persons
+        .into_iter()
+        .map(realize_person)
+        .map(|person| (person.id, person))
+        .unzip();
+
+    let event = super::Event {
+        id: super::EventId(model.guid),
+        number: model.id.as_ref().and_then(serde_json::Value::as_u64),
+        slug: model.slug.unwrap_or_default(),
+        start: model.date,
+        duration: time_to_duration(model.duration),
+        title: model.title,
+        subtitle: model.subtitle.unwrap_or_default(),
+        r#abstract: model.r#abstract.unwrap_or_default(),
+        description: model.description.unwrap_or_default(),
+        room: model.room,
+        track: model.track.unwrap_or_default(),
+        r#type: model.r#type.unwrap_or_default(),
+        language: model.language.unwrap_or_default(),
+        url: model.url.unwrap_or_default(),
+        feedback_url: model.feedback_url,
+        links: model
+            .links
+            .into_iter()
+            .map(|link| (link.title, link.url))
+            .collect(),
+        attachments: model
+            .attachments
+            .into_iter()
+            .map(|attachment| (attachment.title, absolute(&attachment.url, base_url)))
+            .collect(),
+        logo: model
+            .logo
+            .filter(|logo| !logo.is_empty())
+            .map(|logo| absolute(&logo, base_url)),
+        recording: super::Recording {
+            recorded: model.do_not_record.map(|optout| !optout),
+            license: model
+                .recording_license
+                .filter(|license| !license.trim().is_empty()),
+        },
+        persons: person_ids,
+    };
+
+    (event, persons)
+}
+
+fn realize_person(model: model_json::Person) -> super::Person {
+    let name = model.public_name.or(model.name).unwrap_or_default();
+
+    // pretalx only gives out a short code, frab only a numeric ID
+    let id = model.guid.unwrap_or_else(|| {
+        let key = match (&model.code, &model.id) {
+            (Some(code), _) => code.clone(),
+            (None, Some(id)) => id.to_string(),
+            (None, None) => name.clone(),
+        };
+        derive_guid(&key)
+    });
+
+    super::Person {
+        id: super::PersonId(id),
+        number: model.id.as_ref().and_then(serde_json::Value::as_u64),
+        code: model.code,
+        name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::state::schedule::{fixture, Schedule};
+
+    #[test]
+    fn json_matches_xml() {
+        let path = Path::new(fixture::PATH).with_extension("json");
+        let json = Schedule::from_bytes(&fs::read(&path).unwrap(), &path).unwrap();
+        let xml = fixture::schedule();
+
+        assert_eq!(json.conference(), xml.conference());
+        assert_eq!(json.version(), xml.version());
+        assert_eq!(json.days(), xml.days());
+        assert_eq!(json.rooms(), xml.rooms());
+        assert_eq!(json.time_map(), xml.time_map());
+
+        for (_, event) in xml.coords() {
+            assert_eq!(json[&event.id], *event);
+            for person in &event.persons {
+                assert_eq!(json[person], xml[person]);
+                assert_eq!(json.events_of(person), xml.events_of(person));
+            }
+        }
+    }
+}
@@ -0,0 +1,818 @@
+//! Understanding the bits of Markdown and HTML that end up in abstracts and descriptions.
+//!
+//! pretalx takes Markdown there, while some frab instances let HTML through. Neither is parsed
+//! fully, just enough to show emphasis, headings, lists, code and links instead of the raw syntax.
+//! Plain text goes through as it is, keeping its line breaks.
+
+use std::borrow::Cow;
+
+/// HTML tags understood, anything else is dropped while keeping its content.
+const HTML_TAGS: [&str; 23] = [
+    "p",
+    "br",
+    "div",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "strong",
+    "b",
+    "em",
+    "i",
+    "code",
+    "pre",
+    "ul",
+    "ol",
+    "li",
+    "a",
+    "blockquote",
+    "span",
+    "hr",
+    "img",
+];
+
+/// Text split into paragraphs, headings and the like, each consisting of styled spans.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub kind: BlockKind,
+    /// Line breaks to keep are `\n` in the text of the spans.
+    pub spans: Vec<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    Paragraph,
+    /// From 1 for the most important one to 6.
+    Heading(u8),
+    ListItem {
+        /// 0 for items not nested in another list.
+        depth: usize,
+        /// `•` for unordered lists, otherwise the number, like `3.`.
+        marker: String,
+    },
+    /// Preformatted, meant to be shown as-is without wrapping.
+    Code,
+    Quote,
+}
+
+/// How a span is emphasized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+    /// Where the span links to, if it's part of a link.
+    pub link: Option<String>,
+}
+
+impl Document {
+    /// Parses the text as HTML if it contains any tags, otherwise as Markdown.
+    pub fn parse(text: &str) -> Self {
+        match looks_like_html(text) {
+            true => parse_html(text),
+            false => parse_markdown(text),
+        }
+    }
+
+    /// Returns the text and target of every link, in the order they appear in.
+    pub fn links(&self) -> Vec<(String, &str)> {
+        let mut links: Vec<(String, &str)> = Vec::new();
+        let mut previous = None;
+
+        for block in &self.blocks {
+            for span in &block.spans {
+                let Some(url) = span.link.as_deref() else {
+                    previous = None;
+                    continue;
+                };
+
+                // one link might be split over multiple spans due to different styles
+                match links.last_mut() {
+                    Some((text, _)) if previous == Some(url) => text.push_str(&span.text),
+                    _ => links.push((span.text.clone(), url)),
+                }
+                previous = Some(url);
+            }
+            previous = None;
+        }
+
+        for (text, url) in &mut links {
+            *text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                *text = url.to_string();
+            }
+        }
+        links
+    }
+}
+
+/// Collects blocks while either parser walks through the text.
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    current: Option<Block>,
+}
+
+impl Builder {
+    /// Ends the current block and starts a new one of the given kind.
+    fn start(&mut self, kind: BlockKind) {
+        self.finish();
+        self.current = Some(Block {
+            kind,
+            spans: Vec::new(),
+        });
+    }
+
+    /// Appends text to the current block, starting a paragraph if there's none.
+    fn push(&mut self, text: &str, style: Style, link: Option<&str>) {
+        let block = self.current.get_or_insert_with(|| Block {
+            kind: BlockKind::Paragraph,
+            spans: Vec::new(),
+        });
+
+        // whitespace at the start of a line is just from the source
+        let at_line_start = match block.spans.last() {
+            Some(span) => span.text.ends_with('\n'),
+            None => true,
+        };
+        let text = match at_line_start && block.kind != BlockKind::Code {
+            true => text.trim_start_matches([' ', '\t']),
+            false => text,
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        match block.spans.last_mut() {
+            Some(last) if last.style == style && last.link.as_deref() == link => {
+                last.text.push_str(text)
+            }
+            _ => block.spans.push(Span {
+                text: text.to_string(),
+                style,
+                link: link.map(str::to_string),
+            }),
+        }
+    }
+
+    /// Ends the current block, unless nothing was put into it yet, e.g. for a paragraph right
+    /// at the start of a list item.
+    fn paragraph_break(&mut self) {
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|block| !block.spans.is_empty())
+        {
+            self.finish();
+        }
+    }
+
+    fn line_break(&mut self) {
+        if self.current.is_some() {
+            self.push("\n", Style::default(), None);
+        }
+    }
+
+    /// Ends the current block, dropping it if it has no visible content.
+    fn finish(&mut self) {
+        let Some(mut block) = self.current.take() else {
+            return;
+        };
+
+        // trailing line breaks and whitespace would only show up as empty lines
+        while let Some(last) = block.spans.last_mut() {
+            let trimmed = last.text.trim_end().len();
+            last.text.truncate(trimmed);
+            if !last.text.is_empty() {
+                break;
+            }
+            block.spans.pop();
+        }
+
+        if !block.spans.is_empty() {
+            self.blocks.push(block);
+        }
+    }
+
+    fn build(mut self) -> Document {
+        self.finish();
+        Document {
+            blocks: self.blocks,
+        }
+    }
+}
+
+/// Whether there's any tag in the text which is also in [`HTML_TAGS`].
+fn looks_like_html(text: &str) -> bool {
+    text.match_indices('<').any(|(idx, _)| {
+        let tag = text[idx + 1..].trim_start_matches('/');
+        let name = tag
+            .split(|ch: char| !ch.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default();
+        HTML_TAGS
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(name))
+            && tag[name.len()..].starts_with(['>', ' ', '/', '\n'])
+    })
+}
+
+fn parse_markdown(text: &str) -> Document {
+    let mut builder = Builder::default();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.is_empty() {
+            builder.finish();
+            continue;
+        }
+
+        // fenced code blocks are taken over verbatim until the fence closes
+        if let Some(fence) = ["```", "~~~"]
+            .into_iter()
+            .find(|fence| trimmed.starts_with(fence))
+        {
+            builder.start(BlockKind::Code);
+            for line in lines.by_ref() {
+                if line.trim_start().starts_with(fence) {
+                    break;
+                }
+                builder.push(line, Style::default(), None);
+                builder.push("\n", Style::default(), None);
+            }
+            builder.finish();
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|ch| *ch == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with([' ', '\t']) {
+            builder.start(BlockKind::Heading(hashes as u8));
+            let content = trimmed[hashes..].trim().trim_end_matches('#');
+            inline_markdown(&mut builder, content, Style::default(), None);
+            builder.finish();
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            builder.finish();
+            continue;
+        }
+
+        if let Some(quoted) = trimmed.strip_prefix('>') {
+            match &builder.current {
+                Some(Block {
+                    kind: BlockKind::Quote,
+                    ..
+                }) => builder.line_break(),
+                _ => builder.start(BlockKind::Quote),
+            }
+            inline_markdown(&mut builder, quoted.trim_start(), Style::default(), None);
+            continue;
+        }
+
+        if let Some((marker, content)) = list_marker(trimmed) {
+            builder.start(BlockKind::ListItem {
+                depth: indent / 2,
+                marker,
+            });
+            inline_markdown(&mut builder, content, Style::default(), None);
+            continue;
+        }
+
+        // just continuing whatever came before, keeping the line break
+        builder.line_break();
+        inline_markdown(&mut builder, trimmed, Style::default(), None);
+    }
+
+    builder.build()
+}
+
+/// Whether the line is a thematic break like `---` or `***`.
+fn is_rule(line: &str) -> bool {
+    let line: String = line.chars().filter(|ch| !ch.is_whitespace()).collect();
+    line.len() >= 3
+        && ['-', '*', '_']
+            .into_iter()
+            .any(|ch| line.chars().all(|candidate| candidate == ch))
+}
+
+/// Splits off a list marker like `- `, `* ` or `3. `, returning what to display instead of it
+/// and the content after it.
+fn list_marker(line: &str) -> Option<(String, &str)> {
+    if let Some(content) = line
+        .strip_prefix(['-', '*', '+'])
+        .and_then(|rest| rest.strip_prefix(' '))
+    {
+        return Some(("•".to_string(), content));
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let content = line[digits..].strip_prefix(['.', ')'])?.strip_prefix(' ')?;
+    Some((format!("{}.", &line[..digits]), content))
+}
+
+/// Parses emphasis, code and links within one line.
+fn inline_markdown(builder: &mut Builder, text: &str, style: Style, link: Option<&str>) {
+    let mut plain = String::new();
+    let mut rest = text;
+    // `_` only emphasizes at word boundaries, so `snake_case` stays as it is
+    let mut previous: Option<char> = None;
+
+    while let Some(ch) = rest.chars().next() {
+        let after = &rest[ch.len_utf8()..];
+
+        if ch == '\\' {
+            if let Some(escaped) = after.chars().next().filter(char::is_ascii_punctuation) {
+                plain.push(escaped);
+                previous = Some(escaped);
+                rest = &after[escaped.len_utf8()..];
+                continue;
+            }
+        }
+
+        if ch == '`' {
+            if let Some(end) = after.find('`') {
+                flush(builder, &mut plain, style, link);
+                let code = Style {
+                    code: true,
+                    ..style
+                };
+                builder.push(&after[..end], code, link);
+                previous = Some('`');
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        if rest.starts_with("**") || rest.starts_with("__") {
+            let delimiter = &rest[..2];
+            if let Some(end) = rest[2..].find(delimiter).filter(|end| *end > 0) {
+                flush(builder, &mut plain, style, link);
+                let bold = Style {
+                    bold: true,
+                    ..style
+                };
+                inline_markdown(builder, &rest[2..2 + end], bold, link);
+                previous = Some(ch);
+                rest = &rest[2 + end + 2..];
+                continue;
+            }
+        }
+
+        let at_boundary = !previous.is_some_and(char::is_alphanumeric);
+        if (ch == '*' || (ch == '_' && at_boundary)) && !after.starts_with(char::is_whitespace) {
+            let end = after.match_indices(ch).map(|(end, _)| end).find(|end| {
+                let closes_word = !after[end + 1..].starts_with(char::is_alphanumeric);
+                *end > 0 && (ch == '*' || closes_word)
+            });
+            if let Some(end) = end {
+                flush(builder, &mut plain, style, link);
+                let italic = Style {
+                    italic: true,
+                    ..style
+                };
+                inline_markdown(builder, &after[..end], italic, link);
+                previous = Some(ch);
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        // images can't be shown, but at least they can be opened
+        let (image, bracketed) = match rest.strip_prefix("![") {
+            Some(_) => (true, &rest[1..]),
+            None => (false, rest),
+        };
+        if link.is_none() && bracketed.starts_with('[') {
+            if let Some((label, url, len)) = markdown_link(bracketed) {
+                flush(builder, &mut plain, style, link);
+                match (image, label.is_empty()) {
+                    (true, true) => builder.push("image", style, Some(url)),
+                    _ => inline_markdown(builder, label, style, Some(url)),
+                }
+                previous = Some(')');
+                rest = &bracketed[len..];
+                continue;
+            }
+        }
+
+        // autolinks like `<https://example.com>`
+        if ch == '<' && link.is_none() {
+            if let Some(url) = after
+                .split_once('>')
+                .map(|(url, _)| url)
+                .filter(|url| is_url(url))
+            {
+                flush(builder, &mut plain, style, link);
+                builder.push(url, style, Some(url));
+                previous = Some('>');
+                rest = &after[url.len() + 1..];
+                continue;
+            }
+        }
+
+        plain.push(ch);
+        previous = Some(ch);
+        rest = after;
+    }
+
+    flush(builder, &mut plain, style, link);
+}
+
+/// Pushes the text collected so far, so something styled differently can follow.
+fn flush(builder: &mut Builder, plain: &mut String, style: Style, link: Option<&str>) {
+    builder.push(plain, style, link);
+    plain.clear();
+}
+
+/// Parses a link like `[text](url "title")` at the start of `text`, returning the text, the url
+/// and how long the whole link is.
+fn markdown_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = matching(text, '[', ']')?;
+    let target = text[label_end + 1..].strip_prefix('(')?;
+    let target_end = matching(&text[label_end + 1..], '(', ')')?;
+
+    let target = &target[..target_end - 1];
+    let url = target.split_whitespace().next().unwrap_or_default();
+    let url = url.trim_start_matches('<').trim_end_matches('>');
+    if url.is_empty() {
+        return None;
+    }
+
+    Some((&text[1..label_end], url, label_end + 1 + target_end + 1))
+}
+
+/// Finds the index of the bracket closing the one `text` starts with.
+fn matching(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (idx, ch) in text.char_indices() {
+        if ch == open {
+            depth += 1;
+        } else if ch == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+fn is_url(text: &str) -> bool {
+    (text.starts_with("https://") || text.starts_with("http://"))
+        && !text.contains(char::is_whitespace)
+}
+
+fn parse_html(text: &str) -> Document {
+    let mut builder = Builder::default();
+    let mut bold = 0_usize;
+    let mut italic = 0_usize;
+    let mut code = 0_usize;
+    let mut preformatted = false;
+    let mut link: Option<String> = None;
+    // per nested list, the number of the next item, or `None` for unordered ones
+    let mut lists: Vec<Option<usize>> = Vec::new();
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (content, tag) = match rest.find('<') {
+            Some(start) => (&rest[..start], Some(&rest[start..])),
+            None => (rest, None),
+        };
+
+        if !content.is_empty() {
+            let style = Style {
+                bold: bold > 0,
+                italic: italic > 0,
+                code: code > 0 || preformatted,
+            };
+            let content = decode_entities(content);
+            let content = match preformatted {
+                true => content,
+                false => Cow::Owned(collapse_whitespace(&content)),
+            };
+            builder.push(&content, style, link.as_deref());
+        }
+
+        let Some(tag) = tag else { break };
+        if let Some(comment) = tag.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, after)| after);
+            continue;
+        }
+        let Some(end) = tag.find('>') else {
+            // not actually a tag, just a lonely `<`
+            builder.push(&decode_entities(tag), Style::default(), link.as_deref());
+            break;
+        };
+        rest = &tag[end + 1..];
+
+        let tag = &tag[1..end];
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name_len = tag
+            .find(|ch: char| !ch.is_ascii_alphanumeric())
+            .unwrap_or(tag.len());
+        let name = tag[..name_len].to_ascii_lowercase();
+        let attributes = &tag[name_len..];
+
+        let counter = |counter: &mut usize| match closing {
+            true => *counter = counter.saturating_sub(1),
+            false => *counter += 1,
+        };
+
+        match name.as_str() {
+            "p" | "div" => builder.paragraph_break(),
+            "hr" => builder.finish(),
+            "br" => builder.line_break(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => match closing {
+                true => builder.finish(),
+                false => builder.start(BlockKind::Heading(name.as_bytes()[1] - b'0')),
+            },
+            "strong" | "b" => counter(&mut bold),
+            "em" | "i" => counter(&mut italic),
+            "code" => counter(&mut code),
+            "pre" => {
+                preformatted = !closing;
+                match closing {
+                    true => builder.finish(),
+                    false => builder.start(BlockKind::Code),
+                }
+            }
+            "blockquote" => match closing {
+                true => builder.finish(),
+                false => builder.start(BlockKind::Quote),
+            },
+            "ul" | "ol" => {
+                match closing {
+                    true => {
+                        lists.pop();
+                    }
+                    false => lists.push((name == "ol").then(|| {
+                        attribute(attributes, "start")
+                            .and_then(|start| start.parse().ok())
+                            .unwrap_or(1)
+                    })),
+                }
+                builder.finish();
+            }
+            "li" => match closing {
+                true => builder.finish(),
+                false => {
+                    let marker = match lists.last_mut() {
+                        Some(Some(next)) => {
+                            *next += 1;
+                            format!("{}.", *next - 1)
+                        }
+                        _ => "•".to_string(),
+                    };
+                    builder.start(BlockKind::ListItem {
+                        depth: lists.len().saturating_sub(1),
+                        marker,
+                    });
+                }
+            },
+            "a" => {
+                link = match closing {
+                    true => None,
+                    false => attribute(attributes, "href").map(|href| decode_entities(href).into()),
+                }
+            }
+            "img" => {
+                if let Some(src) = attribute(attributes, "src") {
+                    let alt = attribute(attributes, "alt").unwrap_or("image");
+                    builder.push(&decode_entities(alt), Style::default(), Some(src));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    builder.build()
+}
+
+/// Looks up the value of the attribute in something like ` href="…" class=foo`.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    loop {
+        rest = rest.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '/');
+        let (key, after) = rest.split_once('=')?;
+        let after = after.trim_start();
+
+        let (value, after) = match after.chars().next()? {
+            quote @ ('"' | '\'') => after[1..].split_once(quote)?,
+            _ => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+
+        if key.trim().eq_ignore_ascii_case(name) {
+            return Some(value);
+        }
+        rest = after;
+    }
+}
+
+/// Replaces runs of whitespace, including line breaks, by a single space, as browsers do.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for ch in text.chars() {
+        match ch.is_whitespace() && ch != '\u{a0}' {
+            true if in_whitespace => (),
+            true => {
+                collapsed.push(' ');
+                in_whitespace = true;
+            }
+            false => {
+                collapsed.push(ch);
+                in_whitespace = false;
+            }
+        }
+    }
+    collapsed
+}
+
+/// Resolves character references like `&amp;` or `&#8364;`. Unknown ones are left as they are,
+/// ones to control characters other than tabs and line breaks are dropped.
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].split_once(';').and_then(|(name, _)| {
+            let ch = match name {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                _ => {
+                    let number = name.strip_prefix('#')?;
+                    let number = match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => number.parse(),
+                    };
+                    char::from_u32(number.ok()?)?
+                }
+            };
+            Some((ch, name.len() + 2))
+        });
+
+        match entity {
+            Some((ch, len)) => {
+                // something like `&#27;` could otherwise sneak escape sequences into the terminal
+                if !ch.is_control() || ch == '\t' || ch == '\n' {
+                    decoded.push(ch);
+                }
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    Cow::Owned(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of each block, with the styles and links left out.
+    fn texts(document: &Document) -> Vec<String> {
+        document
+            .blocks
+            .iter()
+            .map(|block| block.spans.iter().map(|span| span.text.as_str()).collect())
+            .collect()
+    }
+
+    fn span(text: &str, style: Style) -> Span {
+        Span {
+            text: text.to_string(),
+            style,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn emphasis_but_not_in_words() {
+        let document = Document::parse("*it* and **bold**, but snake_case_name stays");
+        let italic = Style {
+            italic: true,
+            ..Style::default()
+        };
+        let bold = Style {
+            bold: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            document.blocks[0].spans,
+            [
+                span("it", italic),
+                span(" and ", Style::default()),
+                span("bold", bold),
+                span(", but snake_case_name stays", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn links_with_nested_brackets() {
+        let document = Document::parse("see [the [draft] paper](https://example.org/a_(b)) now");
+
+        assert_eq!(texts(&document), ["see the [draft] paper now"]);
+        assert_eq!(
+            document.links(),
+            [("the [draft] paper".to_string(), "https://example.org/a_(b)")]
+        );
+    }
+
+    #[test]
+    fn nested_and_numbered_lists() {
+        let document = Document::parse("- one\n  - two\n7. seven");
+        let kinds: Vec<_> = document.blocks.iter().map(|block| &block.kind).collect();
+
+        assert_eq!(
+            kinds,
+            [
+                &BlockKind::ListItem {
+                    depth: 0,
+                    marker: "•".to_string()
+                },
+                &BlockKind::ListItem {
+                    depth: 1,
+                    marker: "•".to_string()
+                },
+                &BlockKind::ListItem {
+                    depth: 0,
+                    marker: "7.".to_string()
+                },
+            ]
+        );
+        assert_eq!(texts(&document), ["one", "two", "seven"]);
+
+        let document = Document::parse("<ol start=\"3\"><li>three</li><li>four</li></ol>");
+        let markers: Vec<_> = document
+            .blocks
+            .iter()
+            .map(|block| match &block.kind {
+                BlockKind::ListItem { marker, .. } => marker.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(markers, ["3.", "4."]);
+    }
+
+    #[test]
+    fn entities_without_control_characters() {
+        let document =
+            Document::parse("<p>a &amp; b &#8364;&#x41; &bogus; &#0;&#27;[31m&#x9b;red</p>");
+
+        assert_eq!(texts(&document), ["a & b €A &bogus; [31mred"]);
+    }
+
+    #[test]
+    fn preformatted_keeps_whitespace() {
+        let document = Document::parse("<p>before</p><pre>  fn main() {\n      ()\n  }</pre>");
+
+        assert_eq!(document.blocks[1].kind, BlockKind::Code);
+        assert_eq!(texts(&document), ["before", "  fn main() {\n      ()\n  }"]);
+    }
+
+    #[test]
+    fn lone_angle_bracket() {
+        assert_eq!(texts(&Document::parse("<p>1 < 2")), ["1 < 2"]);
+        assert_eq!(texts(&Document::parse("1 < 2 > 0")), ["1 < 2 > 0"]);
+    }
+}
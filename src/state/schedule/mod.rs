@@ -1,40 +1,114 @@
-//! Parsing the XML into something usable in Rust.
+//! Parsing the XML or JSON into something usable in Rust.
 //!
 //! Note that anytime `Id` is mentioned, actually the `guid` attribute is meant, **not** the `id` one.
 //! The pipeline is `XML` → [`model::Schedule`] → [`convert`]'s [`From`] impl → [`Schedule`].
+//! For JSON, it's analogously [`model_json`] and [`convert_json`].
 
+pub mod cache;
 pub mod convert;
+pub mod convert_json;
+pub mod markup;
 pub mod model;
+pub mod model_json;
 
 use std::{
     collections::{BTreeMap, HashMap},
-    fs,
-    io::BufReader,
+    fmt, iter,
     ops::Index,
     path::Path,
+    str::FromStr,
 };
 
-use either::Either;
 use eyre::{Context, Result};
-use time::Duration;
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Time, UtcOffset};
 use uuid::Uuid;
 
 use crate::DateTime;
+use markup::Document;
 
 #[derive(Clone, Debug, Default)]
 pub struct Schedule {
+    conference: Conference,
+    /// Free-form, frab uses a timestamp, pretalx a version number.
+    version: Option<String>,
+
     events: HashMap<EventId, Event>,
     persons: HashMap<PersonId, Person>,
+    /// Which events each person appears in, in chronological order.
+    person_events: HashMap<PersonId, Vec<EventId>>,
+    /// In the order they're first declared in.
+    rooms: Vec<Room>,
+    /// In chronological order.
+    days: Vec<Day>,
 
     time_map: BTreeMap<DateTime, Vec<EventId>>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// What the whole schedule is about.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Conference {
+    pub acronym: String,
+    pub title: String,
+    pub start: Option<DateTime>,
+    pub end: Option<DateTime>,
+    pub url: Option<String>,
+    /// When one conference day ends and the next begins, usually somewhen in the early morning.
+    pub day_change: Option<Time>,
+    /// In the order they're declared in.
+    pub tracks: Vec<Track>,
+}
+
+/// One logical conference day, which doesn't have to end at midnight. A talk at 01:00 still
+/// belongs to the day before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Day {
+    /// Counted by the conference, usually starting at 1.
+    pub index: u32,
+    pub date: Date,
+    pub start: DateTime,
+    pub end: DateTime,
+}
+
+impl Day {
+    pub fn contains(&self, point: DateTime) -> bool {
+        self.start <= point && point < self.end
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Track {
+    /// What [`Event::track`] refers to.
+    pub name: String,
+    /// As given in the schedule, usually `#rrggbb`.
+    pub color: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct EventId(Uuid);
 
+impl fmt::Display for EventId {
+    /// Formats the guid of the event, e.g. for persisting it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for EventId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Event {
     pub id: EventId,
+    /// The numeric `id` attribute frab and pretalx number events with.
+    pub number: Option<u64>,
+    /// Title in lowercase with dashes, as used in URLs. Empty if not given.
+    pub slug: String,
 
     pub start: DateTime,
     pub duration: Duration,
@@ -53,6 +127,11 @@ pub struct Event {
     pub feedback_url: Option<String>,
     /// Displayed text is key, URL is value.
     pub links: BTreeMap<String, String>,
+    /// Files like slides or papers, just like [`Event::links`].
+    pub attachments: BTreeMap<String, String>,
+    /// URL of an image representing the event.
+    pub logo: Option<String>,
+    pub recording: Recording,
 
     pub persons: Vec<PersonId>,
 }
@@ -61,6 +140,54 @@ impl Event {
     pub fn end(&self) -> DateTime {
         self.start + self.duration
     }
+
+    /// Every URL of this event together with what to call it, in the order they're numbered in:
+    /// The event page itself, then [`Event::links`] and [`Event::attachments`], then ones in the
+    /// abstract and description, then the feedback form. Empty and repeated ones are left out.
+    pub fn urls(&self) -> Vec<(String, String)> {
+        self.urls_in(&self.documents())
+    }
+
+    /// The abstract and description, parsed.
+    pub fn documents(&self) -> [Document; 2] {
+        [&self.r#abstract, &self.description].map(|text| Document::parse(text))
+    }
+
+    /// Same as [`Event::urls`], but with [`Event::documents`] already parsed, so they don't have
+    /// to be parsed again when they're shown, too.
+    pub fn urls_in(&self, documents: &[Document; 2]) -> Vec<(String, String)> {
+        let page = ("event page".to_string(), self.url.clone());
+        let links = self
+            .links
+            .iter()
+            .chain(&self.attachments)
+            .map(|(title, url)| (title.clone(), url.clone()));
+        let inline = documents
+            .iter()
+            .flat_map(Document::links)
+            .map(|(title, url)| (title, url.to_string()));
+        let feedback = self
+            .feedback_url
+            .clone()
+            .map(|url| ("feedback".to_string(), url));
+
+        let mut urls: Vec<(String, String)> = Vec::new();
+        for (title, url) in iter::once(page).chain(links).chain(inline).chain(feedback) {
+            if !url.is_empty() && !urls.iter().any(|(_, known)| *known == url) {
+                urls.push((title, url));
+            }
+        }
+        urls
+    }
+}
+
+/// Whether an event is recorded, which decides for some if it's worth attending in person.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Recording {
+    /// [`None`] if the schedule doesn't tell.
+    pub recorded: Option<bool>,
+    /// What the recording is released under, if the schedule tells.
+    pub license: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -69,25 +196,162 @@ pub struct PersonId(Uuid);
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Person {
     pub id: PersonId,
+    /// The numeric `id` attribute frab gives out.
+    pub number: Option<u64>,
+    /// The short code pretalx gives out instead of a number.
+    pub code: Option<String>,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RoomId(Uuid);
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Room {
+    pub id: RoomId,
+    /// What [`Event::room`] refers to.
     pub name: String,
 }
 
 impl Schedule {
-    pub fn from_xml_file(source: impl AsRef<Path>) -> Result<Self> {
-        let source = fs::File::open(source).context("could not open requested schedule")?;
-        let source = BufReader::new(source);
+    /// Parses the schedule read from `source`, detecting whether it's XML or JSON by the file
+    /// extension, or if that's not conclusive, by the first non-whitespace character.
+    pub fn from_bytes(content: &[u8], source: &Path) -> Result<Self> {
+        let is_json = match source.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => true,
+            Some(ext) if ext.eq_ignore_ascii_case("xml") => false,
+            _ => content
+                .iter()
+                .find(|byte| !byte.is_ascii_whitespace())
+                .is_some_and(|byte| *byte == b'{'),
+        };
 
-        let model = model::parse(source).context("could not parse schedule into model")?;
-        let schedule = model.into();
+        let schedule = match is_json {
+            true => model_json::parse(content)
+                .context("could not parse schedule into model")?
+                .into(),
+            false => model::parse(content)
+                .context("could not parse schedule into model")?
+                .into(),
+        };
 
         Ok(schedule)
     }
 
+    /// Pure getter.
+    pub fn conference(&self) -> &Conference {
+        &self.conference
+    }
+
+    /// Looks up the track with the given name, or [`None`] if the conference doesn't declare it.
+    pub fn track(&self, name: &str) -> Option<&Track> {
+        self.conference
+            .tracks
+            .iter()
+            .find(|track| track.name == name)
+    }
+
+    /// Pure getter.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Pure getter.
+    pub fn days(&self) -> &[Day] {
+        &self.days
+    }
+
+    /// Returns the conference day the point in time belongs to, or [`None`] if it's outside of the
+    /// conference.
+    pub fn day_of(&self, point: DateTime) -> Option<&Day> {
+        self.days.iter().find(|day| day.contains(point))
+    }
+
     /// Pure getter.
     pub fn time_map(&self) -> &BTreeMap<DateTime, Vec<EventId>> {
         &self.time_map
     }
 
+    /// Pure getter.
+    pub fn rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    /// Iterates over all events in no particular order.
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.values()
+    }
+
+    /// Iterates over all events in chronological order, together with where to find them.
+    pub fn coords(&self) -> impl DoubleEndedIterator<Item = (TimeCoord, &Event)> {
+        self.time_map.iter().flat_map(move |(row, ids)| {
+            ids.iter()
+                .enumerate()
+                .map(move |(idx, id)| (TimeCoord { row: *row, idx }, &self[id]))
+        })
+    }
+
+    /// Returns the event with the given ID, or [`None`] if it's not part of this schedule.
+    pub fn get(&self, id: &EventId) -> Option<&Event> {
+        self.events.get(id)
+    }
+
+    /// Returns all events whose title, subtitle, abstract, description or speaker names contain
+    /// the given query, ignoring case, in chronological order.
+    pub fn search(&self, query: &str) -> Vec<EventId> {
+        let query = query.to_lowercase();
+        let matches = |text: &str| text.to_lowercase().contains(&query);
+
+        self.time_map
+            .values()
+            .flatten()
+            .filter(|id| {
+                let event = &self[*id];
+                [
+                    &event.title,
+                    &event.subtitle,
+                    &event.r#abstract,
+                    &event.description,
+                ]
+                .into_iter()
+                .any(|text| matches(text))
+                    || event
+                        .persons
+                        .iter()
+                        .any(|person| matches(&self[person].name))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns everyone whose name contains the characters of the query in that order, ignoring
+    /// case and whitespace, sorted by name. So `jdoe` finds "Jane Doe".
+    pub fn persons(&self, query: &str) -> Vec<PersonId> {
+        let query: Vec<char> = query
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        let mut persons: Vec<&Person> = self
+            .persons
+            .values()
+            .filter(|person| {
+                // greedily is good enough to tell whether it's a subsequence
+                let mut name = person.name.chars().flat_map(char::to_lowercase);
+                query.iter().all(|wanted| name.any(|ch| ch == *wanted))
+            })
+            .collect();
+        persons.sort_by_cached_key(|person| (person.name.to_lowercase(), person.id));
+
+        persons.into_iter().map(|person| person.id).collect()
+    }
+
+    /// Returns all events the person appears in, in chronological order.
+    pub fn events_of(&self, id: &PersonId) -> &[EventId] {
+        self.person_events.get(id).map_or(&[], Vec::as_slice)
+    }
+
     /// Returns the first event in this schedule, or `None` if the schedule contains no events.
     pub fn first(&self) -> Option<&Event> {
         let id = self
@@ -100,31 +364,33 @@ impl Schedule {
         Some(&self[id])
     }
 
-    /// Returns the requested _n_-th date and events after to the given date.
-    /// Negative _n_ result in the date and events _before_ the given date.
-    ///
-    /// Returns [`None`] if one of
-    ///
-    /// 1. there is no such event (exceeded range of available events)
-    /// 2. `n == 0` and `to` doesn't point to an existing timeslot start
-    pub fn relative(&mut self, n: isize, to: DateTime) -> Option<(&DateTime, &Vec<EventId>)> {
-        // see which direction we need to look to
-        let mut iter = match n.signum() {
-            -1 => {
-                // negative, so _before_
-                Either::Left(self.time_map.range(..to).rev())
-            }
-            1 => {
-                // positive, so _after_
-                Either::Right(self.time_map.range(to..))
-            }
-            0 => return self.time_map.get_key_value(&to),
-            _ => unreachable!("signum never returns outside of [-1, 1]"),
-        };
+    /// Returns the offset the conference is held in at that point, going by the last event starting
+    /// before it, or the first one if there's none. That way, a switch to or from daylight saving
+    /// time in the middle of the conference is followed. If there are no events, there's no way to
+    /// tell, so it's UTC then.
+    pub fn offset_at(&self, point: DateTime) -> UtcOffset {
+        self.time_map
+            .range(..=point)
+            .next_back()
+            .or_else(|| self.time_map.first_key_value())
+            .map(|(start, _)| start.offset())
+            .unwrap_or(UtcOffset::UTC)
+    }
 
-        let n = n.abs() as usize;
+    /// Looks up where the given event can be found in the [`Schedule::time_map`].
+    /// Returns [`None`] if the event doesn't belong to this schedule.
+    pub fn coord_of(&self, id: EventId) -> Option<TimeCoord> {
+        let event = self.events.get(&id)?;
+        let idx = self
+            .time_map
+            .get(&event.start)?
+            .iter()
+            .position(|candidate| *candidate == id)?;
 
-        iter.nth(n)
+        Some(TimeCoord {
+            row: event.start,
+            idx,
+        })
     }
 }
 
@@ -160,8 +426,113 @@ impl Index<&TimeCoord> for Schedule {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TimeCoord {
     pub row: DateTime,
     pub idx: usize,
 }
+
+/// The small schedule in `data/fixtures`, which the tests all over the crate share.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use super::*;
+
+    pub const PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/fixtures/schedule.xml");
+
+    pub fn schedule() -> Schedule {
+        let content = std::fs::read(PATH).unwrap();
+        Schedule::from_bytes(&content, Path::new(PATH)).unwrap()
+    }
+
+    /// The JSON version of the fixture, with its `conference` object changed by `edit` before
+    /// it's converted. Handy for anything comparing two versions of the schedule.
+    pub fn edited(edit: impl FnOnce(&mut serde_json::Value)) -> Schedule {
+        let path = Path::new(PATH).with_extension("json");
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        edit(&mut json["schedule"]["conference"]);
+        Schedule::from_bytes(json.to_string().as_bytes(), &path).unwrap()
+    }
+
+    /// The event with the given `id` attribute, as their guids only differ in that.
+    pub fn event(id: u8) -> EventId {
+        format!("00000000-0000-4000-8000-{id:012}").parse().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{datetime, offset};
+
+    use super::fixture::{self, event};
+
+    #[test]
+    fn offsets_follow_the_events() {
+        // as if daylight saving time started during the night, so the point in time is the same
+        let schedule = fixture::edited(|conference| {
+            conference["days"][1]["rooms"]["Saal 1"][0]["date"] =
+                "2023-12-28T13:00:00+02:00".into();
+        });
+        assert_eq!(schedule[&event(6)].start, datetime!(2023-12-28 12:00 +1));
+
+        // before anything starts, the first event is all there is to go by
+        assert_eq!(
+            schedule.offset_at(datetime!(2023-12-27 08:00 +1)),
+            offset!(+1)
+        );
+        assert_eq!(
+            schedule.offset_at(datetime!(2023-12-28 11:59 +1)),
+            offset!(+1)
+        );
+        assert_eq!(
+            schedule.offset_at(datetime!(2023-12-28 12:00 +1)),
+            offset!(+2)
+        );
+        assert_eq!(
+            schedule.offset_at(datetime!(2023-12-29 12:00 +1)),
+            offset!(+2)
+        );
+    }
+
+    #[test]
+    fn days_end_at_the_day_change() {
+        let schedule = fixture::schedule();
+        let index = |point| schedule.day_of(point).map(|day| day.index);
+
+        assert_eq!(index(datetime!(2023-12-27 03:59 +1)), None);
+        assert_eq!(index(datetime!(2023-12-27 04:00 +1)), Some(1));
+        assert_eq!(index(schedule[&event(4)].start), Some(1));
+        assert_eq!(index(datetime!(2023-12-28 03:59 +1)), Some(1));
+        assert_eq!(index(datetime!(2023-12-28 04:00 +1)), Some(2));
+        assert_eq!(index(schedule[&event(6)].start), Some(2));
+        assert_eq!(index(datetime!(2023-12-29 04:00 +1)), None);
+    }
+
+    #[test]
+    fn persons_are_found_by_characters_in_order() {
+        let schedule = fixture::schedule();
+        let names = |query| -> Vec<&str> {
+            let ids = schedule.persons(query);
+            ids.iter().map(|id| schedule[id].name.as_str()).collect()
+        };
+
+        assert_eq!(names(""), ["Jane Doe", "Jo Hannes", "John Smith"]);
+        assert_eq!(names("jdoe"), ["Jane Doe"]);
+        assert_eq!(names(" D O E "), ["Jane Doe"]);
+        assert_eq!(names("jo"), ["Jane Doe", "Jo Hannes", "John Smith"]);
+        assert_eq!(names("ohs"), ["Jo Hannes", "John Smith"]);
+        assert_eq!(names("smithj"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn events_of_a_person_are_chronological() {
+        let schedule = fixture::schedule();
+        let jane = schedule.persons("jane doe")[0];
+
+        // the first two start at the same time in different rooms
+        assert_eq!(
+            schedule.events_of(&jane),
+            [event(1), event(5), event(2), event(6)]
+        );
+    }
+}
@@ -5,17 +5,13 @@ use std::io::BufRead;
 
 use eyre::Result;
 use serde::Deserialize;
-use time::Time;
+use time::{Date, Time};
 use uuid::Uuid;
 
 use crate::DateTime;
 
 time::serde::format_description!(time_only, Time, "[hour]:[minute]");
-time::serde::format_description!(
-    version_timestamp,
-    OffsetDateTime,
-    "[year]-[month]-[day] [hour]:[minute]"
-);
+time::serde::format_description!(date_only, Date, "[year]-[month]-[day]");
 
 pub fn parse<R: BufRead>(source: R) -> Result<Schedule> {
     let schedule = quick_xml::de::from_reader(source)?;
@@ -24,8 +20,8 @@ pub fn parse<R: BufRead>(source: R) -> Result<Schedule> {
 
 #[derive(Debug, Deserialize)]
 pub struct Schedule {
-    // #[serde(with = "version_timestamp")]
-    // pub version: OffsetDateTime,
+    /// Usually a timestamp, but not in a format worth parsing, so it's just displayed as-is.
+    pub version: Option<String>,
     pub conference: Conference,
     #[serde(rename = "day")]
     pub days: Vec<Day>,
@@ -40,6 +36,8 @@ pub struct Conference {
     #[serde(with = "time::serde::rfc3339")]
     pub end: DateTime,
     pub url: String,
+    #[serde(with = "time_only::option", default)]
+    pub day_change: Option<Time>,
 
     #[serde(rename = "track")]
     pub tracks: Vec<Track>,
@@ -55,6 +53,14 @@ pub struct Track {
 
 #[derive(Debug, Deserialize)]
 pub struct Day {
+    #[serde(rename = "@index")]
+    pub index: u32,
+    #[serde(rename = "@date", with = "date_only")]
+    pub date: Date,
+    #[serde(rename = "@start", with = "time::serde::rfc3339")]
+    pub start: DateTime,
+    #[serde(rename = "@end", with = "time::serde::rfc3339")]
+    pub end: DateTime,
     #[serde(rename = "room")]
     pub rooms: Vec<Room>,
 }
@@ -73,10 +79,17 @@ pub struct Room {
 pub struct Event {
     #[serde(rename = "@guid")]
     pub guid: Uuid,
+    #[serde(rename = "@id")]
+    pub id: Option<u64>,
     #[serde(with = "time::serde::rfc3339")]
     pub date: DateTime,
+    /// Same as the time of [`Event::date`], which is the one actually used since it has a date and
+    /// an offset, too.
+    #[serde(with = "time_only::option", default)]
+    pub start: Option<Time>,
     #[serde(with = "time_only")]
     pub duration: Time,
+    pub slug: Option<String>,
 
     pub room: String,
     pub title: String,
@@ -92,7 +105,14 @@ pub struct Event {
 
     pub url: String,
     pub feedback_url: Option<String>,
+    pub logo: Option<String>,
     pub links: Links,
+    #[serde(default)]
+    pub attachments: Attachments,
+
+    pub recording: Option<Recording>,
+    /// Older frab versions only have this instead of [`Event::recording`].
+    pub do_not_record: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,10 +121,15 @@ pub struct Persons {
     pub persons: Vec<Person>,
 }
 
+/// pretalx leaves out the guid, but always has a short code.
 #[derive(Debug, Deserialize)]
 pub struct Person {
     #[serde(rename = "@guid")]
-    pub guid: Uuid,
+    pub guid: Option<Uuid>,
+    #[serde(rename = "@id")]
+    pub id: Option<u64>,
+    #[serde(rename = "@code")]
+    pub code: Option<String>,
     #[serde(rename = "$value")]
     pub name: String,
 }
@@ -122,3 +147,25 @@ pub struct Link {
     #[serde(rename = "$value")]
     pub display: String,
 }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Attachments {
+    #[serde(rename = "attachment", default)]
+    pub attachments: Vec<Attachment>,
+}
+
+/// Like [`Link`], but `href` might be relative to the frab instance the schedule is from.
+#[derive(Debug, Deserialize)]
+pub struct Attachment {
+    #[serde(rename = "@href")]
+    pub href: String,
+    #[serde(rename = "$value")]
+    pub display: String,
+}
+
+/// Both are plain text, `optout` being `true` or `false`. Either one might be empty.
+#[derive(Debug, Deserialize)]
+pub struct Recording {
+    pub license: Option<String>,
+    pub optout: Option<String>,
+}
@@ -0,0 +1,128 @@
+//! Parse the JSON export published by frab and pretalx, the sibling of [`super::model`].
+//!
+//! The structure is the same as the XML one, just nested a bit differently and with more fields
+//! being optional, depending on which software generated it.
+
+use std::{collections::BTreeMap, io::Read};
+
+use eyre::Result;
+use serde::Deserialize;
+use time::{Date, Time};
+use uuid::Uuid;
+
+use crate::DateTime;
+
+time::serde::format_description!(time_only, Time, "[hour]:[minute]");
+time::serde::format_description!(date_only, Date, "[year]-[month]-[day]");
+
+pub fn parse<R: Read>(source: R) -> Result<Schedule> {
+    let root: Root = serde_json::from_reader(source)?;
+    Ok(root.schedule)
+}
+
+#[derive(Debug, Deserialize)]
+struct Root {
+    schedule: Schedule,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Schedule {
+    pub version: Option<String>,
+    pub conference: Conference,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Conference {
+    pub acronym: String,
+    pub title: String,
+    /// pretalx only gives a date here, so it's parsed later on and ignored if it doesn't work out.
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub url: Option<String>,
+    #[serde(with = "time_only::option", default)]
+    pub day_change: Option<Time>,
+    #[serde(default)]
+    pub tracks: Vec<Track>,
+    #[serde(default)]
+    pub rooms: Vec<Room>,
+    pub days: Vec<Day>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Track {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Room {
+    pub name: String,
+    pub guid: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Day {
+    pub index: u32,
+    #[serde(with = "date_only")]
+    pub date: Date,
+    /// Not always given, in which case the day is derived from [`Conference::day_change`].
+    #[serde(with = "time::serde::rfc3339::option", default)]
+    pub day_start: Option<DateTime>,
+    #[serde(with = "time::serde::rfc3339::option", default)]
+    pub day_end: Option<DateTime>,
+    /// Room name is key, all events in that room on that day are value.
+    pub rooms: BTreeMap<String, Vec<Event>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Event {
+    pub guid: Uuid,
+    /// Numeric for both frab and pretalx, but nobody promised that.
+    pub id: Option<serde_json::Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub date: DateTime,
+    #[serde(with = "time_only")]
+    pub duration: Time,
+    pub slug: Option<String>,
+
+    pub room: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub language: Option<String>,
+    pub track: Option<String>,
+    #[serde(rename = "type")]
+    pub r#type: Option<String>,
+    #[serde(rename = "abstract")]
+    pub r#abstract: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub persons: Vec<Person>,
+
+    pub url: Option<String>,
+    pub feedback_url: Option<String>,
+    pub logo: Option<String>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    /// Only given by pretalx, for uploaded files like slides. Might be relative to the instance.
+    #[serde(default)]
+    pub attachments: Vec<Link>,
+
+    pub do_not_record: Option<bool>,
+    pub recording_license: Option<String>,
+}
+
+/// frab and pretalx disagree on pretty much every field here, so they're all optional.
+#[derive(Debug, Deserialize)]
+pub struct Person {
+    pub guid: Option<Uuid>,
+    pub code: Option<String>,
+    pub id: Option<serde_json::Value>,
+    pub name: Option<String>,
+    pub public_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Link {
+    pub url: String,
+    pub title: String,
+}
@@ -0,0 +1,75 @@
+//! Where the user left off, so the next launch continues right there instead of at the first
+//! event.
+//!
+//! Sessions are stored per schedule source, as a selection in one schedule means nothing in
+//! another one.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::DateTime;
+
+use super::{fetch::Source, schedule::EventId, store::Mode};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub mode: Mode,
+    /// By ID and not by coordinate, so it survives the event being rescheduled.
+    pub selected: EventId,
+    #[serde(with = "time::serde::rfc3339")]
+    pub grid_scroll_at: DateTime,
+    pub single_scroll_at: u16,
+    /// So the search mode can be restored, too.
+    #[serde(default)]
+    pub search_query: String,
+}
+
+impl Session {
+    /// Where the session for the given source is stored, if the platform has a place for it.
+    pub fn path(source: &Source) -> Option<PathBuf> {
+        let dir = dirs::data_dir()?;
+        let location = match source {
+            // canonicalizing so the same file opened from elsewhere still finds its session
+            Source::Path(path) => path.canonicalize().unwrap_or_else(|_| path.clone()),
+            Source::Url(url) => url.into(),
+        };
+        let key = Uuid::new_v5(&Uuid::NAMESPACE_URL, location.to_string_lossy().as_bytes());
+        Some(
+            dir.join("inoe")
+                .join("sessions")
+                .join(format!("{key}.json")),
+        )
+    }
+
+    /// Reads the session from the given file, returning [`None`] if there is none yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()))
+            }
+        };
+
+        let session = serde_json::from_str(&content)
+            .with_context(|| format!("invalid session in {}", path.display()))?;
+        Ok(Some(session))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("could not create session directory")?;
+        }
+
+        let content =
+            serde_json::to_string_pretty(self).context("session serialization failure")?;
+        fs::write(path, content).with_context(|| format!("could not write {}", path.display()))
+    }
+}
@@ -1,11 +1,30 @@
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use either::Either;
 use eyre::{Context, ContextCompat, Result};
 use ratatui::layout::Direction;
+use serde::{Deserialize, Serialize};
+use time::UtcOffset;
 
 use crate::{config::Config, DateTime, To, VerticalDirection};
 
 use super::{
-    schedule::TimeCoord,
-    schedule::{self, Schedule},
+    diff::ScheduleDiff,
+    export::{self, Scope},
+    favourites::Favourites,
+    fetch::{self, Source},
+    filter::Filter,
+    grid::{GridLayout, ScheduleGrid, DEFAULT_COLUMNS},
+    link,
+    schedule::{self, EventId, PersonId, Schedule, TimeCoord},
+    session::Session,
     Action, Update,
 };
 
@@ -13,9 +32,26 @@ pub struct Store {
     state: State,
 }
 
+/// A schedule loaded in the background, or why loading it failed.
+pub type Loaded = Result<Box<Schedule>, String>;
+
 pub struct State {
     /// The actual bare data we want to display.
     pub schedule: Schedule,
+    /// Where the schedule came from, so it can be loaded again.
+    pub source: Source,
+    /// Where downloads and parsed schedules are kept, if there's any place for them.
+    cache_dir: Option<PathBuf>,
+    /// Where exports from within the viewer end up.
+    export_dir: PathBuf,
+    /// Where to remember the mode, selection and scroll positions across launches, if anywhere.
+    session_path: Option<PathBuf>,
+    /// What to open links of events with.
+    opener: link::Opener,
+
+    /// The offset the user wants times to be displayed in, or [`None`] to follow the conference.
+    /// See [`State::local`].
+    offset: Option<UtcOffset>,
 
     /// What mode the user is currently looking at.
     pub mode: Mode,
@@ -23,10 +59,27 @@ pub struct State {
     /// What event is selected at the moment, and where to find it.
     pub selection: TimeCoord,
 
+    /// How the events are laid out in columns, as seen in the grid mode.
+    pub grid: ScheduleGrid,
+    /// Which events are shown in the grid and can be selected by moving around.
+    pub filter: Filter,
+
+    /// Events the user starred, used for planning their own agenda.
+    pub favourites: Favourites,
+
+    /// Short message about the outcome of the last action, if it has one worth telling.
+    pub notice: Option<String>,
+
     /// State specific to the grid mode.
     pub grid_state: GridState,
+    /// State specific to the search results mode.
+    pub search_state: SearchState,
     /// State specific to the single/detail mode.
     pub single_state: SingleState,
+    /// State specific to the speakers mode.
+    pub speakers_state: SpeakersState,
+    /// State specific to the changes mode, telling what the last reload did.
+    pub changes_state: ChangesState,
 }
 
 impl Store {
@@ -39,6 +92,22 @@ impl Store {
     pub fn state(&self) -> &State {
         &self.state
     }
+
+    /// Writes everything persistent to disk that changed since the last call.
+    pub fn flush(&mut self) -> Result<()> {
+        self.state.favourites.flush()
+    }
+
+    /// Remembers where the user is right now for the next launch, if there's a place for that.
+    pub fn save_session(&self) -> Result<()> {
+        let Some(path) = &self.state.session_path else {
+            return Ok(());
+        };
+        self.state
+            .session()
+            .save(path)
+            .context("session saving failure")
+    }
 }
 
 impl Update for Store {
@@ -49,29 +118,122 @@ impl Update for Store {
 
 impl State {
     pub fn new(config: &Config) -> Result<Self> {
-        let schedule =
-            Schedule::from_xml_file(&config.schedule).context("schedule construction failure")?;
+        let cache_dir = config.cache_dir.clone().or_else(fetch::default_cache_dir);
+        let source = config
+            .schedule
+            .clone()
+            .context("no schedule given to look at")?;
+        let schedule = load(&source, cache_dir.as_deref())?;
 
-        let first_event = schedule
-            .first()
+        let offset = config
+            .timezone
+            .unwrap_or_default()
+            .resolve()
+            .context("timezone resolution failure")?;
+
+        let filter: Filter = config.filters.iter().cloned().collect();
+
+        // the filter might hide everything, but that's no reason to not show anything at all
+        let (selection, _) = schedule
+            .coords()
+            .find(|(_, event)| filter.matches(event))
+            .or_else(|| schedule.coords().next())
             .context("schedule is empty, nothing to display")?;
-        let selection = TimeCoord {
-            row: first_event.start,
-            idx: 0,
-        };
 
         let grid_state = GridState {
-            scroll_at: first_event.start,
+            scroll_at: selection.row,
+            layout: GridLayout::default(),
+            columns: config
+                .grid_columns
+                .map_or(DEFAULT_COLUMNS, NonZeroUsize::get),
+        };
+        let single_state = SingleState {
+            scroll_at: 0,
+            back_to: Mode::Grid,
         };
-        let single_state = SingleState { scroll_at: 0 };
+        let search_state = SearchState::default();
+        let speakers_state = SpeakersState {
+            matches: schedule.persons(""),
+            ..SpeakersState::default()
+        };
+        let grid = ScheduleGrid::new(&schedule, &filter, grid_state.layout, grid_state.columns);
 
-        Ok(Self {
+        // starring still works without a place to keep them, just not across launches
+        let favourites = match config.favourites.clone().or_else(Favourites::default_path) {
+            Some(path) => Favourites::load(path).context("favourites loading failure")?,
+            None => Favourites::in_memory(),
+        };
+        let notice = favourites.path().is_none().then(|| {
+            "no data directory found, favourites are only kept until quitting".to_string()
+        });
+
+        let session_path = Session::path(&source);
+
+        let mut state = Self {
             schedule,
+            source,
+            cache_dir,
+            export_dir: config.export_dir.clone(),
+            session_path,
+            opener: config.opener.clone().unwrap_or_default(),
+            offset,
             mode: Mode::default(),
             selection,
+            grid,
+            filter,
+            favourites,
+            notice,
             grid_state,
             single_state,
-        })
+            search_state,
+            speakers_state,
+            changes_state: ChangesState::default(),
+        };
+
+        // a broken session is annoying, but not worth refusing to start over
+        let session = state.session_path.as_deref().map(Session::load);
+        if let Some(Ok(Some(session))) = session {
+            state.restore(session);
+        }
+
+        Ok(state)
+    }
+
+    /// Captures where the user currently is.
+    pub fn session(&self) -> Session {
+        Session {
+            mode: self.mode,
+            selected: self.selected_event().id,
+            grid_scroll_at: self.grid_state.scroll_at,
+            single_scroll_at: self.single_state.scroll_at,
+            search_query: self.search_state.query.clone(),
+        }
+    }
+
+    /// Continues where the session left off, as far as the schedule still allows.
+    fn restore(&mut self, session: Session) {
+        if let Some(coord) = self.schedule.coord_of(session.selected) {
+            self.selection = coord;
+        }
+        self.grid_state.scroll_at = session.grid_scroll_at;
+        self.single_state.scroll_at = session.single_scroll_at;
+
+        self.search_state.matches = self.schedule.search(&session.search_query);
+        self.search_state.query = session.search_query;
+
+        self.mode = match session.mode {
+            // the diff is long gone
+            Mode::Changes => Mode::Grid,
+            mode => mode,
+        };
+
+        // the filter might have changed since then, hiding the selection
+        self.refilter();
+
+        // who was selected isn't remembered, but the event tells a good guess
+        if self.mode == Mode::Speakers {
+            self.show_speakers();
+        }
     }
 
     /// Returns the currently selected event.
@@ -81,53 +243,469 @@ impl State {
         &self.schedule[&self.selection]
     }
 
+    /// Converts the point in time into the timezone the user wants to see.
+    pub fn local(&self, point: DateTime) -> DateTime {
+        let offset = self
+            .offset
+            .unwrap_or_else(|| self.schedule.offset_at(point));
+        point.to_offset(offset)
+    }
+
+    /// Returns if the point in time is on the same day as right now, in the displayed timezone.
+    pub fn is_today(&self, point: DateTime) -> bool {
+        self.local(DateTime::now_utc()).date() == self.local(point).date()
+    }
+
+    /// Rebuilds everything depending on the filter or grid layout. Moves the selection to the next
+    /// visible event if the current one got hidden.
+    fn refilter(&mut self) {
+        self.grid = ScheduleGrid::new(
+            &self.schedule,
+            &self.filter,
+            self.grid_state.layout,
+            self.grid_state.columns,
+        );
+
+        if self.filter.matches(self.selected_event()) {
+            return;
+        }
+
+        let current = self.selection.row;
+        let visible = || {
+            self.schedule
+                .coords()
+                .filter(|(_, event)| self.filter.matches(event))
+        };
+        let target = visible()
+            .find(|(coord, _)| coord.row >= current)
+            .or_else(|| visible().next_back());
+
+        if let Some((target, _)) = target {
+            self.selection = target;
+            self.grid_state.scroll_at = target.row;
+        }
+    }
+
+    /// Starts loading the schedule again from its source on another thread, since that might mean
+    /// downloading it. Once done, the outcome arrives through the receiver, ready to be passed on
+    /// as [`Action::Reloaded`].
+    pub fn reload_in_background(&self) -> Receiver<Loaded> {
+        let source = self.source.clone();
+        let cache_dir = self.cache_dir.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let schedule = load(&source, cache_dir.as_deref())
+                .and_then(|schedule| {
+                    // the selection has to point somewhere
+                    schedule
+                        .first()
+                        .context("new schedule is empty, keeping the current one")?;
+                    Ok(Box::new(schedule))
+                })
+                .map_err(|err| format!("{err:#}"));
+            // if nobody's listening anymore, the app is exiting anyway
+            let _ = sender.send(schedule);
+        });
+
+        receiver
+    }
+
+    /// Takes over the schedule loaded by [`State::reload_in_background`], remembering what changed
+    /// in the [`State::changes_state`]. If loading failed, the current schedule is kept.
+    ///
+    /// Switches to the changes mode afterwards, unless `quiet` is set and nothing changed.
+    fn reloaded(&mut self, quiet: bool, schedule: Loaded) {
+        let schedule = match schedule {
+            Ok(schedule) => *schedule,
+            Err(err) => {
+                self.changes_state.error = Some(err);
+                if !quiet {
+                    self.mode = Mode::Changes;
+                }
+                return;
+            }
+        };
+
+        let diff = ScheduleDiff::between(&self.schedule, &schedule);
+        let selected = self.selected_event().id;
+        let old_row = self.selection.row;
+        self.schedule = schedule;
+
+        // stay on the same event if possible, otherwise on whatever is closest in time
+        self.selection = self.schedule.coord_of(selected).unwrap_or_else(|| {
+            let (coord, _) = self
+                .schedule
+                .coords()
+                .find(|(coord, _)| coord.row >= old_row)
+                .or_else(|| self.schedule.coords().next_back())
+                .expect("schedule to be checked for emptiness");
+            coord
+        });
+        self.grid_state.scroll_at = self.selection.row;
+        self.search_state.matches = self.schedule.search(&self.search_state.query);
+        // unlike with filtering, the selected event should stay the same, so the person has to go
+        self.speakers_state.matches = self.schedule.persons(&self.speakers_state.query);
+        let matches = &self.speakers_state.matches;
+        self.speakers_state.selected = self
+            .speakers_state
+            .selected
+            .filter(|id| matches.contains(id));
+        self.refilter();
+
+        if !quiet || !diff.is_empty() {
+            self.mode = Mode::Changes;
+        }
+        self.changes_state = ChangesState {
+            diff,
+            error: None,
+            scroll_at: 0,
+        };
+    }
+
+    /// Renders the events in the given scope as iCalendar, in chronological order.
+    pub fn export(&self, scope: Scope) -> String {
+        let events = self.schedule.coords().map(|(_, event)| event);
+        let events = match scope {
+            Scope::Favourites => {
+                Either::Left(events.filter(|event| self.favourites.contains(&event.id)))
+            }
+            Scope::Filtered => Either::Right(events.filter(|event| self.filter.matches(event))),
+        };
+
+        export::ics(events)
+    }
+
+    /// Writes the export into the export directory, telling the user where it went.
+    fn export_to_file(&mut self, scope: Scope) {
+        let path = self.export_dir.join(scope.file_name());
+        let result = fs::write(&path, self.export(scope));
+
+        self.notice = Some(match result {
+            Ok(()) => format!("exported to {}", path.display()),
+            Err(err) => format!("could not export to {}: {err}", path.display()),
+        });
+    }
+
+    /// Opens the _n_-th URL of the selected event, counting from 1, see [`schedule::Event::urls`].
+    fn open_link(&mut self, n: usize) {
+        let urls = self.selected_event().urls();
+        let Some((_, url)) = n.checked_sub(1).and_then(|idx| urls.get(idx)) else {
+            self.notice = Some(format!("there's no link {n}"));
+            return;
+        };
+
+        if let Err(err) = self.opener.open(url) {
+            self.notice = Some(format!("could not open {url}: {err}"));
+        }
+    }
+
+    /// Switches to the speakers mode, selecting the first person of the selected event. If it has
+    /// none, whoever was selected last stays so.
+    fn show_speakers(&mut self) {
+        self.mode = Mode::Speakers;
+
+        let id = self
+            .selected_event()
+            .persons
+            .first()
+            .or(self.speakers_state.selected.as_ref())
+            .or(self.speakers_state.matches.first())
+            .copied();
+        if let Some(id) = id {
+            self.select_speaker(id);
+        }
+    }
+
+    /// Selects the person in the speakers mode, along with their first event unless one of theirs
+    /// is selected already. If the speaker filter hides them, it's cleared.
+    fn select_speaker(&mut self, id: PersonId) {
+        if !self.speakers_state.matches.contains(&id) {
+            self.speakers_state.query.clear();
+            self.speakers_state.matches = self.schedule.persons("");
+        }
+        self.speakers_state.selected = Some(id);
+
+        let events = self.schedule.events_of(&id);
+        if events.contains(&self.selected_event().id) {
+            return;
+        }
+        let Some(first) = events.first() else {
+            return;
+        };
+
+        self.selection = self
+            .schedule
+            .coord_of(*first)
+            .expect("person index to only contain events of the schedule");
+        self.grid_state.scroll_at = self.selection.row;
+    }
+
+    /// Only lists the speakers matching the query. If the selected one isn't among them anymore,
+    /// the first one is selected instead.
+    fn filter_speakers(&mut self, query: String) {
+        self.speakers_state.matches = self.schedule.persons(&query);
+        self.speakers_state.query = query;
+
+        let matches = &self.speakers_state.matches;
+        if self
+            .speakers_state
+            .selected
+            .is_some_and(|id| matches.contains(&id))
+        {
+            return;
+        }
+
+        self.speakers_state.selected = None;
+        if let Some(first) = matches.first().copied() {
+            self.select_speaker(first);
+        }
+    }
+
+    /// Moves through the speakers with up and down, and through the events of the selected one
+    /// with left and right.
+    fn select_in_speakers(&mut self, dir: To) {
+        let state = &self.speakers_state;
+        let current = state
+            .selected
+            .and_then(|id| state.matches.iter().position(|known| *known == id));
+
+        let target = match (dir, current) {
+            (To::Left | To::Right, _) => {
+                let Some(id) = state.selected else {
+                    return;
+                };
+                let amount = match dir {
+                    To::Left => -1,
+                    _ => 1,
+                };
+                let events = self.schedule.events_of(&id).to_vec();
+                self.scroll_list(&events, amount, false);
+                return;
+            }
+            (To::Up, Some(idx)) => idx.checked_sub(1),
+            (To::Below, Some(idx)) => Some(idx + 1),
+            // nobody selected yet, so let's start at the top
+            (To::Up | To::Below, None) => Some(0),
+        };
+
+        let Some(target) = target.and_then(|idx| state.matches.get(idx)).copied() else {
+            // out of range, just stay where we are
+            return;
+        };
+        self.select_speaker(target);
+    }
+
+    /// Moves the selection to the first visible event of the next or previous day that has any.
+    fn jump_day(&mut self, direction: VerticalDirection) {
+        let days = self.schedule.days();
+        let current = days.iter().position(|day| day.contains(self.selection.row));
+
+        let mut candidates = match (direction, current) {
+            (VerticalDirection::Down, Some(idx)) => Either::Left(days[idx + 1..].iter()),
+            (VerticalDirection::Up, Some(idx)) => Either::Right(days[..idx].iter().rev()),
+            // outside of any day, so let's just go by time
+            (VerticalDirection::Down, None) => Either::Left(
+                days[days.partition_point(|day| day.start <= self.selection.row)..].iter(),
+            ),
+            (VerticalDirection::Up, None) => Either::Right(
+                days[..days.partition_point(|day| day.end <= self.selection.row)]
+                    .iter()
+                    .rev(),
+            ),
+        };
+
+        let target = candidates.find_map(|day| {
+            self.schedule
+                .coords()
+                .skip_while(|(coord, _)| coord.row < day.start)
+                .take_while(|(coord, _)| coord.row < day.end)
+                .find(|(_, event)| self.filter.matches(event))
+        });
+
+        let Some((target, _)) = target else {
+            // no more days in that direction, so let's just stay where we are
+            return;
+        };
+
+        self.selection = target;
+        self.grid_state.scroll_at = target.row;
+    }
+
+    /// Returns all starred events of this schedule in chronological order, noting down which ones
+    /// overlap with another starred event.
+    pub fn agenda(&self) -> Vec<AgendaEntry<'_>> {
+        let mut events = self
+            .favourites
+            .iter()
+            .filter_map(|id| self.schedule.get(id))
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| (event.start, event.end(), event.id));
+
+        events
+            .iter()
+            .map(|event| AgendaEntry {
+                event,
+                clashes: events.iter().any(|other| {
+                    other.id != event.id && other.start < event.end() && event.start < other.end()
+                }),
+            })
+            .collect()
+    }
+
+    /// Tells for each room what's running at `now` and what starts next, in the order the rooms
+    /// are declared. Hidden events are ignored, and rooms with neither are left out.
+    pub fn now_and_next(&self, now: DateTime) -> Vec<RoomStatus<'_>> {
+        self.schedule
+            .rooms()
+            .iter()
+            .map(|room| {
+                let events = || {
+                    self.schedule
+                        .coords()
+                        .map(|(_, event)| event)
+                        .filter(|event| event.room == room.name && self.filter.matches(event))
+                };
+
+                // if events overlap, the one starting last is probably the one actually running
+                let current = events().rfind(|event| event.start <= now && now < event.end());
+                let next = events().find(|event| now < event.start);
+
+                RoomStatus {
+                    room: &room.name,
+                    current,
+                    next,
+                }
+            })
+            .filter(|status| status.current.is_some() || status.next.is_some())
+            .collect()
+    }
+
+    /// Moves the selection to the previous (`amount < 0`) or next event in the given list, which
+    /// is assumed to be chronologically ordered.
+    ///
+    /// If `wrap` is set, going past either end continues at the other one.
+    fn scroll_list(&mut self, ids: &[EventId], amount: isize, wrap: bool) {
+        let current = self.selected_event();
+
+        let target = match ids.iter().position(|id| *id == current.id) {
+            Some(idx) if wrap => {
+                let len = ids.len() as isize;
+                ids.get((idx as isize + amount).rem_euclid(len) as usize)
+            }
+            Some(idx) => idx
+                .checked_add_signed(amount.signum())
+                .and_then(|idx| ids.get(idx)),
+            // the selection is not in the list, so let's just go by time
+            None if amount < 0 => ids
+                .iter()
+                .rev()
+                .find(|id| self.schedule[*id].start < current.start)
+                .or_else(|| ids.last().filter(|_| wrap)),
+            None => ids
+                .iter()
+                .find(|id| self.schedule[*id].start >= current.start)
+                .or_else(|| ids.first().filter(|_| wrap)),
+        };
+
+        let Some(target) = target.copied() else {
+            // out of range, just stay where we are
+            return;
+        };
+
+        self.selection = self
+            .schedule
+            .coord_of(target)
+            .expect("list to only contain events of the schedule");
+        self.grid_state.scroll_at = self.selection.row;
+    }
+
     fn scroll(&mut self, direction: Direction, amount: isize) {
         if amount == 0 {
             // valid, but no effect
             return;
         }
 
-        let current_line = self
-            .schedule
-            .time_map()
-            .get(&self.selection.row)
-            .expect("selected row to be valid");
-
         match direction {
-            Direction::Horizontal => {
-                let new_idx = self.selection.idx as isize + amount;
+            Direction::Horizontal if self.grid_state.layout == GridLayout::Rooms => {
+                // columns actually mean something here, so let's stay in the timeframe
+                let id = self.selected_event().id;
+                let Some(column) = self.grid.column_of(self.selection.row, id) else {
+                    return;
+                };
 
-                if (0..current_line.len() as isize).contains(&new_idx) {
-                    // still in the line! just set it
-                    self.selection.idx = new_idx as usize;
+                let left = amount.is_negative();
+                let Some(target) = self
+                    .grid
+                    .horizontal_neighbor(self.selection.row, column, left)
+                else {
                     return;
-                }
+                };
+
+                let coord = self
+                    .schedule
+                    .coord_of(target)
+                    .expect("grid to only contain events of the schedule");
+                self.selection = coord;
+                self.grid_state.scroll_at = coord.row;
+            }
+            Direction::Horizontal => {
+                // just walk through all events in chronological order, skipping hidden ones
+                let current = self.selection;
+                let coords = self.schedule.coords();
+                let coords = match amount.is_negative() {
+                    true => Either::Left(coords.rev()),
+                    false => Either::Right(coords),
+                };
 
-                // oh well, guess we need to adjust the line
-                let row_diff = new_idx.signum();
-                let target = self.schedule.relative(row_diff, self.selection.row);
+                let target = coords
+                    .skip_while(|(coord, _)| *coord != current)
+                    .skip(1)
+                    .filter(|(_, event)| self.filter.matches(event))
+                    .nth(amount.unsigned_abs() - 1);
 
-                let Some((target_row, target_events)) = target else {
+                let Some((target, _)) = target else {
                     // oh no, out of range! let's just stay where we are then
                     return;
                 };
 
-                // otherwise we're good! let's set it
-                self.selection.row = *target_row;
-                self.grid_state.scroll_at = *target_row;
-                self.selection.idx = match row_diff {
-                    1 => 0,
-                    -1 => target_events.len() - 1,
-                    _ => unreachable!(),
-                }
+                self.selection = target;
+                self.grid_state.scroll_at = target.row;
+            }
+            Direction::Vertical => {
+                // events not fitting into any column anymore are treated like being just right of
+                // the last one, so it's still possible to move away from them
+                let id = self.selected_event().id;
+                let column = self
+                    .grid
+                    .column_of(self.selection.row, id)
+                    .unwrap_or(self.grid.width().saturating_sub(1));
+
+                let up = amount.is_negative();
+                let Some(target) = self
+                    .grid
+                    .vertical_neighbor(self.selection.row, column, id, up)
+                else {
+                    // nothing there, so let's just stay where we are
+                    return;
+                };
+
+                let coord = self
+                    .schedule
+                    .coord_of(target)
+                    .expect("grid to only contain events of the schedule");
+                self.selection = coord;
+                self.grid_state.scroll_at = coord.row;
             }
-            Direction::Vertical => todo!(),
         }
     }
 }
 
 impl Update for State {
     fn update(&mut self, action: Action) {
+        // whatever the last action said is outdated now
+        self.notice = None;
+
         // generally we can forward all actions
 
         // except for
@@ -136,40 +714,249 @@ impl Update for State {
             Action::Scroll(_) => match self.mode {
                 Mode::Grid => self.grid_state.update(action),
                 Mode::Single => self.single_state.update(action),
+                Mode::Changes => self.changes_state.update(action),
+                Mode::Agenda | Mode::Search | Mode::Now | Mode::Speakers => (),
             },
-            // switching modes
+            // switching modes, where the speakers mode needs to know whose events to list
+            Action::SwitchTo(Mode::Speakers) => self.show_speakers(),
+            // and the single mode where to go back to
+            Action::SwitchTo(Mode::Single) => {
+                if self.mode != Mode::Single {
+                    self.single_state.back_to = self.mode;
+                }
+                self.mode = Mode::Single;
+            }
             Action::SwitchTo(new_mode) => {
                 self.mode = new_mode;
             }
-            // changing event selection
+            Action::Back => {
+                self.mode = match self.mode {
+                    Mode::Single => self.single_state.back_to,
+                    _ => Mode::Grid,
+                };
+            }
+            // changing event selection, which in lists only goes through the listed events
+            Action::Select(dir) if matches!(self.mode, Mode::Agenda | Mode::Search) => {
+                let amount = match dir {
+                    To::Up => -1,
+                    To::Below => 1,
+                    To::Left | To::Right => return,
+                };
+
+                let ids = match self.mode {
+                    Mode::Search => self.search_state.matches.clone(),
+                    _ => self.agenda().iter().map(|entry| entry.event.id).collect(),
+                };
+                self.scroll_list(&ids, amount, false);
+            }
+            Action::Select(dir) if self.mode == Mode::Speakers => self.select_in_speakers(dir),
             Action::Select(dir) => match dir {
                 To::Left => self.scroll(Direction::Horizontal, -1),
                 To::Right => self.scroll(Direction::Horizontal, 1),
                 To::Up => self.scroll(Direction::Vertical, -1),
                 To::Below => self.scroll(Direction::Vertical, 1),
             },
+            Action::SelectEvent(id) => {
+                // it's where the user is looking already, so no need to scroll
+                if let Some(coord) = self.schedule.coord_of(id) {
+                    self.selection = coord;
+                }
+            }
+            Action::ToggleFavourite => {
+                let id = self.selected_event().id;
+                self.favourites.toggle(id);
+            }
+            Action::ToggleFilter(criterion) => {
+                self.filter.toggle(criterion);
+                self.refilter();
+            }
+            Action::ClearFilter => {
+                self.filter.clear();
+                self.refilter();
+            }
+            Action::ToggleGridLayout => {
+                self.grid_state.layout = match self.grid_state.layout {
+                    GridLayout::Packed => GridLayout::Rooms,
+                    GridLayout::Rooms => GridLayout::Packed,
+                };
+                self.refilter();
+            }
+            // searching, which jumps right to the first match after the selection
+            Action::Search(query) => {
+                let matches = self.schedule.search(&query);
+                if matches.is_empty() {
+                    // an empty list would only leave the previous selection to open
+                    self.notice = Some(format!("no matches for `{query}`"));
+                    return;
+                }
+
+                self.search_state.matches = matches;
+                self.search_state.query = query;
+                self.mode = Mode::Search;
+
+                let matches = self.search_state.matches.clone();
+                if !matches.iter().any(|id| *id == self.selected_event().id) {
+                    self.scroll_list(&matches, 1, true);
+                }
+            }
+            Action::CycleMatch(dir) => {
+                let amount = match dir {
+                    VerticalDirection::Up => -1,
+                    VerticalDirection::Down => 1,
+                };
+                let matches = self.search_state.matches.clone();
+                self.scroll_list(&matches, amount, true);
+            }
+            // the dispatcher does the actual loading, see `State::reload_in_background`
+            Action::Reload { quiet } => {
+                if !quiet {
+                    self.notice = Some("reloading…".to_string());
+                }
+            }
+            Action::Reloaded { quiet, schedule } => self.reloaded(quiet, schedule),
+            Action::JumpDay(direction) => self.jump_day(direction),
+            Action::Export(scope) => self.export_to_file(scope),
+            Action::OpenLink(n) => self.open_link(n),
+            Action::ShowSpeaker(id) => {
+                self.mode = Mode::Speakers;
+                self.select_speaker(id);
+            }
+            Action::FilterSpeakers(query) => self.filter_speakers(query),
             // otherwise, just tell both about it
             _ => {
-                self.grid_state.update(action);
+                self.grid_state.update(action.clone());
                 self.single_state.update(action);
             }
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Mode {
     /// Overview over all events and their chronological order.
     #[default]
     Grid,
     /// One event in all detail.
     Single,
+    /// All starred events in chronological order.
+    Agenda,
+    /// All events matching the last search query.
+    Search,
+    /// What changed on the last reload of the schedule.
+    Changes,
+    /// What's running right now and what comes next, per room.
+    Now,
+    /// Everyone speaking, sorted by name, and what events the selected one appears in.
+    Speakers,
+}
+
+impl Mode {
+    pub const ALL: [Self; 7] = [
+        Self::Grid,
+        Self::Single,
+        Self::Agenda,
+        Self::Search,
+        Self::Changes,
+        Self::Now,
+        Self::Speakers,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grid => "grid",
+            Self::Single => "single",
+            Self::Agenda => "agenda",
+            Self::Search => "search",
+            Self::Changes => "changes",
+            Self::Now => "now",
+            Self::Speakers => "speakers",
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let known = Self::ALL.map(Self::name).join(", ");
+                format!("unknown mode `{s}`, expected one of {known}")
+            })
+    }
+}
+
+/// One starred event as listed in the agenda.
+#[derive(Copy, Clone, Debug)]
+pub struct AgendaEntry<'state> {
+    pub event: &'state schedule::Event,
+    /// If this event overlaps with any other starred event.
+    pub clashes: bool,
+}
+
+/// What's going on in one room at one point in time, as listed in the now mode.
+#[derive(Copy, Clone, Debug)]
+pub struct RoomStatus<'state> {
+    pub room: &'state str,
+    /// The event running at the moment.
+    pub current: Option<&'state schedule::Event>,
+    /// The first event starting afterwards.
+    pub next: Option<&'state schedule::Event>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchState {
+    /// What the user searched for last.
+    pub query: String,
+    /// All events matching [`SearchState::query`] in chronological order.
+    pub matches: Vec<EventId>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpeakersState {
+    /// What the speakers are filtered by, see [`Schedule::persons`].
+    pub query: String,
+    /// Everyone matching [`SpeakersState::query`], sorted by name.
+    pub matches: Vec<PersonId>,
+    /// Whose events are listed, if anyone's.
+    pub selected: Option<PersonId>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChangesState {
+    /// Differences between the schedule before and after the last successful reload.
+    pub diff: ScheduleDiff,
+    /// Why the last reload failed, if it did.
+    pub error: Option<String>,
+    /// Topmost line of where the scroll currently is.
+    pub scroll_at: u16,
+}
+
+impl Update for ChangesState {
+    fn update(&mut self, action: Action) {
+        match action {
+            Action::Scroll(VerticalDirection::Down) => {
+                self.scroll_at = self.scroll_at.saturating_add(1)
+            }
+            Action::Scroll(VerticalDirection::Up) => {
+                self.scroll_at = self.scroll_at.saturating_sub(1)
+            }
+            _ => (),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct GridState {
     /// Topmost point in time of where the scroll currently is.
     pub scroll_at: DateTime,
+    /// How events are assigned to columns.
+    pub layout: GridLayout,
+    /// How many columns [`GridLayout::Packed`] spreads events over.
+    pub columns: usize,
 }
 
 impl Update for GridState {
@@ -184,6 +971,8 @@ impl Update for GridState {
 pub struct SingleState {
     /// Topmost line of where the scroll currently is.
     pub scroll_at: u16,
+    /// Which mode the event was opened from, so going back returns there.
+    pub back_to: Mode,
 }
 
 impl Update for SingleState {
@@ -199,3 +988,253 @@ impl Update for SingleState {
         }
     }
 }
+
+/// Fetches the schedule if needed and parses it.
+fn load(source: &Source, cache_dir: Option<&Path>) -> Result<Schedule> {
+    let path = source
+        .resolve(cache_dir)
+        .context("schedule fetching failure")?;
+    schedule::cache::load(&path, cache_dir).context("schedule construction failure")
+}
+
+/// The [`State`] the tests all over the crate start out with.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::fs;
+
+    use clap::Parser;
+
+    use super::State;
+    use crate::{config::Config, state::schedule::fixture};
+
+    /// A state showing the fixture schedule, keeping favourites and caches in a fresh directory
+    /// named after `name`. `args` are added to the command line.
+    pub fn state(name: &str, args: &[&str]) -> State {
+        let dir = std::env::temp_dir().join(format!("inoe-store-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let favourites = dir.join("favourites");
+        let cache_dir = dir.join("cache");
+
+        let base = [
+            "inoe",
+            fixture::PATH,
+            "--favourites",
+            favourites.to_str().unwrap(),
+            "--cache-dir",
+            cache_dir.to_str().unwrap(),
+        ];
+        let config = Config::try_parse_from(base.iter().chain(args)).unwrap();
+        State::new(&config).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::{fixture::state, *};
+    use crate::state::schedule::fixture::{self, event};
+
+    fn selected(state: &State) -> EventId {
+        state.selected_event().id
+    }
+
+    #[test]
+    fn vertical_selection_stays_at_the_edges() {
+        let mut state = state("edges", &[]);
+        assert_eq!(selected(&state), event(1));
+
+        state.update(Action::Select(To::Up));
+        assert_eq!(selected(&state), event(1));
+
+        state.update(Action::SelectEvent(event(6)));
+        state.update(Action::Select(To::Below));
+        assert_eq!(selected(&state), event(6));
+    }
+
+    #[test]
+    fn vertical_selection_leaves_overflowed_events() {
+        let mut state = state("overflow", &["--grid-columns", "1"]);
+        state.update(Action::SelectEvent(event(5)));
+        assert_eq!(state.grid.column_of(state.selection.row, event(5)), None);
+
+        state.update(Action::Select(To::Below));
+        assert_eq!(selected(&state), event(2));
+        state.update(Action::Select(To::Up));
+        assert_eq!(selected(&state), event(1));
+    }
+
+    #[test]
+    fn favourite_toggles_the_selected_event() {
+        let mut state = state("favourite", &[]);
+
+        state.update(Action::ToggleFavourite);
+        assert!(state.favourites.contains(&event(1)));
+
+        state.update(Action::SelectEvent(event(2)));
+        state.update(Action::ToggleFavourite);
+        state.update(Action::SelectEvent(event(1)));
+        state.update(Action::ToggleFavourite);
+        assert_eq!(
+            state.favourites.iter().copied().collect::<Vec<_>>(),
+            [event(2)]
+        );
+    }
+
+    #[test]
+    fn agenda_marks_overlapping_favourites() {
+        let mut state = state("agenda", &[]);
+        for id in [1, 2, 3, 6] {
+            state.favourites.toggle(event(id));
+        }
+
+        let agenda: Vec<_> = state
+            .agenda()
+            .iter()
+            .map(|entry| (entry.event.id, entry.clashes))
+            .collect();
+        assert_eq!(
+            agenda,
+            [
+                (event(1), false),
+                (event(2), true),
+                (event(3), true),
+                (event(6), false),
+            ]
+        );
+
+        // one ending right when the other starts is fine
+        for id in [1, 3, 5] {
+            state.favourites.toggle(event(id));
+        }
+        assert!(state.agenda().iter().all(|entry| !entry.clashes));
+    }
+
+    #[test]
+    fn back_returns_to_where_single_was_opened_from() {
+        let mut state = state("back", &[]);
+
+        state.update(Action::SwitchTo(Mode::Agenda));
+        state.update(Action::SwitchTo(Mode::Single));
+        state.update(Action::SwitchTo(Mode::Single));
+        state.update(Action::Back);
+        assert_eq!(state.mode, Mode::Agenda);
+
+        state.update(Action::Back);
+        assert_eq!(state.mode, Mode::Grid);
+    }
+
+    #[test]
+    fn reloading_keeps_the_selected_event() {
+        let mut state = state("reload", &[]);
+        state.update(Action::SelectEvent(event(2)));
+
+        let rescheduled = fixture::edited(|conference| {
+            conference["days"][0]["rooms"]["Saal 1"][1]["date"] =
+                "2023-12-27T14:00:00+01:00".into();
+        });
+        state.update(Action::Reloaded {
+            quiet: true,
+            schedule: Ok(Box::new(rescheduled)),
+        });
+
+        assert_eq!(selected(&state), event(2));
+        assert_eq!(state.selection.row, datetime!(2023-12-27 14:00 +1));
+        assert_eq!(state.mode, Mode::Changes);
+        assert_eq!(state.changes_state.diff.changed.len(), 1);
+    }
+
+    #[test]
+    fn reloading_moves_on_from_removed_events() {
+        let mut state = state("reload-removed", &[]);
+        state.update(Action::SelectEvent(event(4)));
+
+        let removed = fixture::edited(|conference| {
+            conference["days"][0]["rooms"]["Saal 1"]
+                .as_array_mut()
+                .unwrap()
+                .remove(3);
+        });
+        state.update(Action::Reloaded {
+            quiet: false,
+            schedule: Ok(Box::new(removed)),
+        });
+
+        assert_eq!(selected(&state), event(6));
+    }
+
+    #[test]
+    fn quiet_reloads_only_show_up_if_needed() {
+        let mut state = state("reload-quiet", &[]);
+
+        state.update(Action::Reloaded {
+            quiet: true,
+            schedule: Ok(Box::new(fixture::edited(|_| ()))),
+        });
+        assert_eq!(state.mode, Mode::Grid);
+
+        state.update(Action::Reloaded {
+            quiet: true,
+            schedule: Err("offline".to_string()),
+        });
+        assert_eq!(state.mode, Mode::Grid);
+        assert_eq!(state.changes_state.error.as_deref(), Some("offline"));
+        assert!(state.schedule.get(&event(1)).is_some());
+    }
+
+    #[test]
+    fn now_and_next_per_room() {
+        let mut state = state("now", &[]);
+        let statuses = |state: &State, now| {
+            state
+                .now_and_next(now)
+                .iter()
+                .map(|status| {
+                    let id = |event: Option<&schedule::Event>| event.map(|event| event.id);
+                    (status.room.to_string(), id(status.current), id(status.next))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            statuses(&state, datetime!(2023-12-27 09:00 +1)),
+            [
+                ("Saal 1".to_string(), None, Some(event(1))),
+                ("Saal 2".to_string(), None, Some(event(5))),
+            ]
+        );
+        // the event starting last wins if they overlap, and rooms with nothing left are left out
+        assert_eq!(
+            statuses(&state, datetime!(2023-12-27 11:45 +1)),
+            [("Saal 1".to_string(), Some(event(3)), Some(event(4)))]
+        );
+
+        state.update(Action::ToggleFilter("track=Security".parse().unwrap()));
+        assert_eq!(
+            statuses(&state, datetime!(2023-12-27 11:45 +1)),
+            [("Saal 1".to_string(), Some(event(2)), Some(event(4)))]
+        );
+    }
+
+    #[test]
+    fn jumping_days_goes_by_the_day_change() {
+        let mut state = state("jump-day", &[]);
+
+        // the event after midnight still belongs to the first day
+        state.update(Action::SelectEvent(event(4)));
+        state.update(Action::JumpDay(VerticalDirection::Up));
+        assert_eq!(selected(&state), event(4));
+        state.update(Action::JumpDay(VerticalDirection::Down));
+        assert_eq!(selected(&state), event(6));
+        state.update(Action::JumpDay(VerticalDirection::Down));
+        assert_eq!(selected(&state), event(6));
+        state.update(Action::JumpDay(VerticalDirection::Up));
+        assert_eq!(selected(&state), event(1));
+
+        // days without anything passing the filter are skipped
+        state.update(Action::ToggleFilter("track=Art & Beauty".parse().unwrap()));
+        assert_eq!(selected(&state), event(5));
+        state.update(Action::JumpDay(VerticalDirection::Down));
+        assert_eq!(selected(&state), event(5));
+    }
+}
@@ -0,0 +1,71 @@
+//! Which timezone times are displayed in, and what counts as "today".
+//!
+//! Only offsets are supported, as that's what the schedule carries anyway. The conference's own
+//! timezone follows the offsets of its events, so it handles a daylight saving time switch if
+//! the schedule does. Its name, like `Europe/Berlin`, is not looked up.
+
+use std::str::FromStr;
+
+use eyre::{Context, Result};
+use time::UtcOffset;
+
+#[cfg(doc)]
+use super::schedule::Schedule;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeZone {
+    /// Whatever the schedule itself uses.
+    #[default]
+    Conference,
+    /// Whatever the system is set to.
+    Local,
+    Fixed(UtcOffset),
+}
+
+impl TimeZone {
+    /// Finds out the actual offset to display times in. [`None`] means it depends on the point in
+    /// time, see [`Schedule::offset_at`].
+    ///
+    /// Needs to be called before any threads are spawned, as the local offset can't be determined
+    /// soundly otherwise on some platforms.
+    pub fn resolve(self) -> Result<Option<UtcOffset>> {
+        match self {
+            Self::Conference => Ok(None),
+            Self::Local => UtcOffset::current_local_offset()
+                .map(Some)
+                .context("could not determine local timezone"),
+            Self::Fixed(offset) => Ok(Some(offset)),
+        }
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = String;
+
+    /// Accepts `conference`, `local`, `utc` or an offset like `+01:00`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!("expected `conference`, `local`, `utc` or an offset like `+01:00`, got `{s}`")
+        };
+
+        match s.to_ascii_lowercase().as_str() {
+            "conference" => return Ok(Self::Conference),
+            "local" => return Ok(Self::Local),
+            "utc" | "z" => return Ok(Self::Fixed(UtcOffset::UTC)),
+            _ => (),
+        }
+
+        let (sign, offset) = match (s.strip_prefix('+'), s.strip_prefix('-')) {
+            (Some(offset), _) => (1, offset),
+            (_, Some(offset)) => (-1, offset),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+        let hours: i8 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i8 = minutes.parse().map_err(|_| invalid())?;
+
+        UtcOffset::from_hms(sign * hours, sign * minutes, 0)
+            .map(Self::Fixed)
+            .map_err(|_| invalid())
+    }
+}
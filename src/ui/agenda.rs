@@ -0,0 +1,77 @@
+//! The user's personal agenda, listing only starred events in chronological order.
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::state::store::{Mode, State};
+
+use super::{
+    helper_span,
+    keymap::{Command, Keymap},
+    DATETIME_FORMAT_LONG, DATETIME_FORMAT_SHORT,
+};
+
+pub struct View<'state> {
+    pub state: &'state State,
+    /// For telling how to star events if there are none yet.
+    pub keymap: &'state Keymap,
+}
+
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let agenda = self.state.agenda();
+
+        if agenda.is_empty() {
+            // events are starred in the grid usually, so that's where the key matters
+            let hint = match self.keymap.first_key(Mode::Grid, Command::Favourite) {
+                Some(key) => Line::from(vec![
+                    helper_span("nothing starred yet, press "),
+                    Span::raw(key.to_string()),
+                    helper_span(" on an event to add it here"),
+                ]),
+                None => Line::from(helper_span("nothing starred yet")),
+            };
+            frame.render_widget(Paragraph::new(hint).alignment(Alignment::Center), area);
+            return;
+        }
+
+        let selected = self.state.selected_event();
+        let selected_idx = agenda
+            .iter()
+            .position(|entry| entry.event.id == selected.id);
+
+        let items = agenda.iter().map(|entry| {
+            let event = entry.event;
+            let start = self
+                .state
+                .local(event.start)
+                .format(DATETIME_FORMAT_LONG)
+                .unwrap();
+            let end = self
+                .state
+                .local(event.end())
+                .format(DATETIME_FORMAT_SHORT)
+                .unwrap();
+
+            let mut line = vec![
+                Span::raw(start),
+                helper_span(" – "),
+                Span::raw(end),
+                Span::raw("  "),
+                Span::raw(format!("{:<16}", event.room)),
+                Span::raw(event.title.as_str()).bold(),
+            ];
+            if entry.clashes {
+                line.push(Span::raw("  clash").red());
+            }
+
+            ListItem::new(Line::from(line))
+        });
+
+        let mut list_state = ListState::default().with_selected(selected_idx);
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::new().reversed()),
+            area,
+            &mut list_state,
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! What the last reload of the schedule changed, or why it failed.
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::state::{schedule::Event, store::State};
+
+use super::{color::Theme, helper_span, DATETIME_FORMAT_LONG};
+
+pub struct View<'state> {
+    pub state: &'state State,
+}
+
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let changes = &self.state.changes_state;
+        let diff = &changes.diff;
+
+        let mut lines = vec![Line::from(vec![
+            helper_span("reloaded from "),
+            Span::raw(self.state.source.to_string()),
+        ])];
+
+        // a failed reload didn't find out whether anything changed
+        match &changes.error {
+            Some(error) => {
+                lines.push(Line::from(Span::raw(format!("failed: {error}")).red()));
+                if !diff.is_empty() {
+                    lines.push(
+                        helper_span("showing the changes of the last successful reload").into(),
+                    );
+                }
+            }
+            None if diff.is_empty() => {
+                lines.push(Line::default());
+                lines.push(helper_span("nothing changed").into());
+            }
+            None => (),
+        }
+
+        if !diff.added.is_empty() {
+            lines.extend(heading("added", diff.added.len()));
+            lines.extend(
+                diff.added
+                    .iter()
+                    .map(|id| event_line(self.state, &self.state.schedule[id])),
+            );
+        }
+
+        if !diff.removed.is_empty() {
+            lines.extend(heading("removed", diff.removed.len()));
+            lines.extend(diff.removed.iter().map(|event| {
+                let mut line = event_line(self.state, event);
+                line.patch_style(Style::new().crossed_out());
+                line
+            }));
+        }
+
+        if !diff.changed.is_empty() {
+            lines.extend(heading("changed", diff.changed.len()));
+            for change in &diff.changed {
+                let old = &change.old;
+                let new = &self.state.schedule[&old.id];
+                lines.push(event_line(self.state, new));
+
+                if change.rescheduled {
+                    let start = self
+                        .state
+                        .local(old.start)
+                        .format(DATETIME_FORMAT_LONG)
+                        .unwrap();
+                    let minutes = old.duration.whole_minutes();
+                    lines.push(was(format!("{start} for {minutes} min")));
+                }
+                if change.moved_room {
+                    lines.push(was(format!("in {}", old.room)));
+                }
+                if change.renamed {
+                    lines.push(was(format!("titled {}", old.title)));
+                }
+            }
+        }
+
+        let block = Block::default().borders(Borders::ALL).title(" changes ");
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((changes.scroll_at, 0)),
+            area,
+        );
+    }
+}
+
+/// Separates the sections, with an empty line before each one.
+fn heading(name: &str, count: usize) -> [Line<'static>; 2] {
+    [
+        Line::default(),
+        Line::from(Span::raw(format!("{name} ({count})")).bold()),
+    ]
+}
+
+fn event_line<'event>(state: &State, event: &'event Event) -> Line<'event> {
+    let start = state
+        .local(event.start)
+        .format(DATETIME_FORMAT_LONG)
+        .unwrap();
+
+    Line::from(vec![
+        Span::raw(start),
+        Span::raw("  "),
+        Span::raw(format!("{:<16}", event.room)),
+        Span::raw(event.title.as_str()).bold(),
+    ])
+}
+
+/// Describes what an event was like before the reload.
+fn was(previously: String) -> Line<'static> {
+    Line::from(vec![
+        helper_span("    was "),
+        Span::styled(previously, Theme::current().helper()),
+    ])
+}
@@ -0,0 +1,230 @@
+//! Turning the colours given in the schedule into something the terminal can display.
+//!
+//! Not every terminal supports truecolor, so the colours are degraded to the nearest one of the
+//! 256-colour palette or even the basic 16 colours, depending on what the environment claims.
+
+use std::{env, sync::OnceLock};
+
+use clap::ValueEnum;
+use ratatui::style::{Color, Style, Stylize};
+use serde::Deserialize;
+
+use crate::state::schedule::Schedule;
+
+/// Levels of each channel in the 6×6×6 colour cube of the 256-colour palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The basic 16 colours, as xterm displays them by default.
+const BASIC: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::Red, [205, 0, 0]),
+    (Color::Green, [0, 205, 0]),
+    (Color::Yellow, [205, 205, 0]),
+    (Color::Blue, [0, 0, 238]),
+    (Color::Magenta, [205, 0, 205]),
+    (Color::Cyan, [0, 205, 205]),
+    (Color::Gray, [229, 229, 229]),
+    (Color::DarkGray, [127, 127, 127]),
+    (Color::LightRed, [255, 0, 0]),
+    (Color::LightGreen, [0, 255, 0]),
+    (Color::LightYellow, [255, 255, 0]),
+    (Color::LightBlue, [92, 92, 255]),
+    (Color::LightMagenta, [255, 0, 255]),
+    (Color::LightCyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+/// Set once when the UI starts, see [`Theme::install`].
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Which colours to use for everything that isn't given by the schedule itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// For terminals with a dark background.
+    #[default]
+    Dark,
+    /// For terminals with a light background, where yellow is barely readable.
+    Light,
+    /// No colours at all, not even for tracks. Only bold and dimmed text.
+    Monochrome,
+}
+
+impl Theme {
+    /// Makes this the theme of the whole UI. Only the first call has any effect.
+    pub fn install(self) {
+        let _ = THEME.set(self);
+    }
+
+    /// Returns the installed theme, or the default one if none was installed yet.
+    pub fn current() -> Self {
+        THEME.get().copied().unwrap_or_default()
+    }
+
+    /// For hints and other text that's only there to help.
+    pub fn helper(self) -> Style {
+        match self {
+            Self::Dark | Self::Light => Style::new().dark_gray(),
+            Self::Monochrome => Style::new().dim(),
+        }
+    }
+
+    /// For starred events.
+    pub fn favourite(self) -> Style {
+        match self {
+            Self::Dark => Style::new().yellow(),
+            Self::Light => Style::new().magenta(),
+            Self::Monochrome => Style::new().bold(),
+        }
+    }
+
+    /// For code in abstracts and descriptions.
+    pub fn code(self) -> Style {
+        match self {
+            Self::Dark => Style::new().cyan(),
+            Self::Light => Style::new().blue(),
+            Self::Monochrome => Style::new(),
+        }
+    }
+}
+
+/// How many colours the terminal can display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette {
+    TrueColor,
+    Indexed,
+    Basic,
+}
+
+impl Palette {
+    /// Guesses the palette from the `COLORTERM` and `TERM` environment variables. Only done once,
+    /// the environment isn't going to change while running.
+    pub fn detect() -> Self {
+        static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+        *PALETTE.get_or_init(|| {
+            let colorterm = env::var("COLORTERM").unwrap_or_default();
+            let term = env::var("TERM").unwrap_or_default();
+
+            if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+                Self::TrueColor
+            } else if term.contains("256color") {
+                Self::Indexed
+            } else {
+                Self::Basic
+            }
+        })
+    }
+
+    /// Returns the colour closest to the given RGB one that this palette has.
+    pub fn nearest(self, rgb: [u8; 3]) -> Color {
+        match self {
+            Self::TrueColor => Color::Rgb(rgb[0], rgb[1], rgb[2]),
+            Self::Indexed => nearest_indexed(rgb),
+            Self::Basic => {
+                let (color, _) = BASIC
+                    .iter()
+                    .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+                    .expect("basic palette to be non-empty");
+                *color
+            }
+        }
+    }
+}
+
+/// Returns the colour of the given track in the schedule, if it has a valid one.
+pub fn track_color(schedule: &Schedule, track: &str) -> Option<Color> {
+    if Theme::current() == Theme::Monochrome {
+        return None;
+    }
+
+    let color = schedule.track(track)?.color.as_deref()?;
+    let rgb = parse_hex(color)?;
+    Some(Palette::detect().nearest(rgb))
+}
+
+/// Parses colours in the form of `#rrggbb`.
+pub fn parse_hex(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx * 2..idx * 2 + 2], 16).ok();
+    Some([channel(0)?, channel(1)?, channel(2)?])
+}
+
+/// Picks the closer one of the colour cube and the grayscale ramp.
+fn nearest_indexed(rgb: [u8; 3]) -> Color {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|idx| CUBE_LEVELS[*idx].abs_diff(channel))
+            .expect("cube levels to be non-empty")
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = [r, g, b].map(|idx| CUBE_LEVELS[idx]);
+    let cube_idx = 16 + 36 * r + 6 * g + b;
+
+    // the ramp goes from 8 to 238 in steps of 10
+    let average = rgb.iter().map(|channel| u32::from(*channel)).sum::<u32>() / 3;
+    let step = (average.saturating_sub(3) / 10).min(23);
+    let gray = (8 + 10 * step) as u8;
+    let gray_idx = 232 + step as usize;
+
+    let idx = match distance(rgb, cube) <= distance(rgb, [gray; 3]) {
+        true => cube_idx,
+        false => gray_idx,
+    };
+    Color::Indexed(idx as u8)
+}
+
+/// Squared euclidean distance, which is good enough for comparing.
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::schedule::fixture;
+
+    #[test]
+    fn only_full_hex_colours_are_parsed() {
+        assert_eq!(parse_hex("#18386b"), Some([0x18, 0x38, 0x6b]));
+        assert_eq!(parse_hex("#F9B000"), Some([0xf9, 0xb0, 0x00]));
+
+        for invalid in ["18386b", "#fff", "#18386b00", "#gggggg", "#ä1234"] {
+            assert_eq!(parse_hex(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn colours_are_degraded_to_the_palette() {
+        let orange = [0xf9, 0xb0, 0x00];
+        assert_eq!(
+            Palette::TrueColor.nearest(orange),
+            Color::Rgb(0xf9, 0xb0, 0x00)
+        );
+        assert_eq!(Palette::Basic.nearest(orange), Color::Yellow);
+        assert_eq!(Palette::Basic.nearest([250, 10, 10]), Color::LightRed);
+
+        // cube and grayscale ramp, whichever is closer
+        assert_eq!(Palette::Indexed.nearest([255, 0, 0]), Color::Indexed(196));
+        assert_eq!(Palette::Indexed.nearest([0, 0, 0]), Color::Indexed(16));
+        assert_eq!(
+            Palette::Indexed.nearest([128, 128, 128]),
+            Color::Indexed(244)
+        );
+    }
+
+    #[test]
+    fn only_declared_tracks_have_a_colour() {
+        let schedule = fixture::schedule();
+
+        assert!(track_color(&schedule, "Security").is_some());
+        assert_eq!(track_color(&schedule, "Crypto"), None);
+    }
+}
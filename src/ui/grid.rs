@@ -1,120 +1,211 @@
 //! Overview over all events in a schedule.
 
-use std::{collections::BTreeMap, iter};
+use std::iter;
 
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{prelude::*, widgets::*};
+use time::{format_description::FormatItem, macros::format_description};
 
 use crate::{
     state::{
-        schedule,
+        grid::ScheduleGrid,
+        schedule::EventId,
         store::{Mode, State},
     },
-    Action, DateTime, To,
+    Action,
 };
 
-use super::{wrap, TerminalEvent, DATETIME_FORMAT_LONG};
-
-const COLUMNS: usize = 7;
+use super::{
+    color::{track_color, Theme},
+    helper_span, wrap, DATETIME_FORMAT_LONG, FAVOURITE_MARKER,
+};
 
 pub struct View<'state> {
     pub state: &'state State,
+    /// Where each visible event was drawn, so clicks can be mapped back to them.
+    cells: Vec<(Rect, EventId)>,
 }
 
-impl<'state> super::View for View<'state> {
-    fn draw(&mut self, frame: &mut Frame<'_>) {
-        let grid = ScheduleGrid::new(&self.state.schedule);
-        grid.render(&self.state, frame);
+impl<'state> View<'state> {
+    pub fn new(state: &'state State) -> Self {
+        Self {
+            state,
+            cells: Vec::new(),
+        }
     }
+}
 
-    fn process(&mut self, event: super::TerminalEvent) -> Option<crate::Action> {
-        let action = match event {
-            TerminalEvent::Key(KeyEvent {
-                code,
-                kind: KeyEventKind::Press,
-                ..
-            }) => match code {
-                KeyCode::Enter => Action::SwitchTo(Mode::Single),
-                KeyCode::Char('k') => Action::Select(To::Up),
-                KeyCode::Char('j') => Action::Select(To::Below),
-                _ => return None,
-            },
-            _ => return None,
-        };
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let days = self.state.schedule.days();
+        if days.is_empty() {
+            self.cells = self.state.grid.render(self.state, frame, area);
+            return;
+        }
+
+        let layout = Layout::default()
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+
+        let titles = days.iter().map(|day| {
+            let weekday = day.date.format(WEEKDAY_FORMAT).unwrap();
+            format!("day {} · {weekday}", day.index)
+        });
+        let selected = days
+            .iter()
+            .position(|day| day.contains(self.state.selection.row));
+        let mut tabs = Tabs::new(titles.collect()).highlight_style(Style::new().reversed());
+        if let Some(selected) = selected {
+            tabs = tabs.select(selected);
+        }
+        frame.render_widget(tabs, layout[0]);
 
-        Some(action)
+        self.cells = self.state.grid.render(self.state, frame, layout[1]);
     }
-}
 
-/// A fully "simulated" schedule, where each timeslot is assigned.
-///
-/// **Note:** The current implementation for this is horribly inefficient and has a runtime of
-/// _O_(_nm_), where _n_ is the count of events and _m_ the number of concurrently occuring events.
-/// I'm sure there's some better and smarter way to do this implicitly and still create a table,
-/// but this works for the moment.
-#[derive(Debug, Default)]
-pub struct ScheduleGrid {
-    timeline: BTreeMap<DateTime, SlottedVec<schedule::EventId, COLUMNS>>,
+    fn click(&mut self, column: u16, row: u16, double: bool) -> Option<Action> {
+        let (_, id) = self.cells.iter().find(|(cell, _)| {
+            (cell.left()..cell.right()).contains(&column)
+                && (cell.top()..cell.bottom()).contains(&row)
+        })?;
+
+        // the first click of the two selected it already
+        Some(match double {
+            true => Action::SwitchTo(Mode::Single),
+            false => Action::SelectEvent(*id),
+        })
+    }
 }
 
+const WEEKDAY_FORMAT: &[FormatItem<'static>] = format_description!("[weekday repr:short]");
+
+/// Drawn in the track colour on the left edge of each cell.
+const TRACK_BORDER: &str = "▎";
+
+/// Narrowest a room column may become before the grid starts scrolling horizontally.
+const MIN_ROOM_WIDTH: u16 = 20;
+
+/// Height of each row in the grid.
+const ROW_HEIGHT: u16 = 3;
+
 impl ScheduleGrid {
-    fn new(base: &schedule::Schedule) -> Self {
-        let mut active_events = SlottedVec::<_, COLUMNS>::new();
-        let mut grid = Self::default();
-
-        for (now, just_starting) in base.time_map() {
-            // cull inactive events
-            active_events.retain(|(_, end)| &*end > now);
-
-            // insert all new ones
-            active_events.extend(just_starting.into_iter().map(|event| {
-                let event = &base[event];
-                (event.id, event.end())
-            }));
-
-            // note down anything that's active now
-            grid.timeline.insert(
-                *now,
-                active_events
-                    .iter()
-                    .map(|slot| slot.map(|(event, _end)| event.clone()))
-                    .collect(),
-            );
-        }
+    /// Draws the grid, returning where each visible event ended up.
+    fn render(&self, state: &State, frame: &mut Frame<'_>, area: Rect) -> Vec<(Rect, EventId)> {
+        let selected = state.selected_event();
 
-        grid
-    }
+        // rooms are not packed, so there might be far too many to show all at once
+        // in that case only show a "page" of them, namely the one containing the selection
+        let (first_column, column_count) = match self.headers.is_empty() {
+            true => (0, self.width()),
+            false => {
+                let fitting = usize::from(area.width.saturating_sub(17) / MIN_ROOM_WIDTH).max(1);
+                let selected_column = self
+                    .column_of(state.selection.row, selected.id)
+                    .unwrap_or_default();
+                (selected_column / fitting * fitting, fitting)
+            }
+        };
+        let columns = first_column..(first_column + column_count).min(self.width());
 
-    fn render(&self, state: &State, frame: &mut Frame<'_>) {
         let mut widths = vec![Constraint::Length(17)];
-        widths.extend(iter::repeat(Constraint::Ratio(1, 9)).take(COLUMNS));
+        match self.headers.is_empty() {
+            true => {
+                let ratio = Constraint::Ratio(1, self.width() as u32 + 2);
+                widths.extend(iter::repeat(ratio).take(self.width()))
+            }
+            false => widths.extend(
+                columns
+                    .clone()
+                    .map(|_| Constraint::Ratio(1, column_count as u32)),
+            ),
+        }
 
-        let cell_width = Layout::default()
+        // same as the table lays them out, with one cell of spacing in-between
+        let column_areas: Vec<Rect> = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(&widths)
-            .split(frame.size())[1]
-            .width;
+            .constraints(itertools::intersperse(
+                widths.iter().copied(),
+                Constraint::Length(1),
+            ))
+            .split(area)
+            .iter()
+            .step_by(2)
+            .skip(1)
+            .copied()
+            .collect();
+        let cell_width = column_areas.first().map_or(0, |cell| cell.width);
 
         // fetch only the relevant part of the timeline
         // rendering the *whole* timeline would be far too laggy
-        let relevant_timeline = self
-            .timeline
-            .range(state.grid_state.scroll_at..)
-            .take(usize::from(frame.size().height / 3 + 1));
+        let relevant_timeline = || {
+            self.timeline
+                .range(state.grid_state.scroll_at..)
+                .take(usize::from(area.height / ROW_HEIGHT + 1))
+        };
 
-        let selected = state.selected_event();
+        let header_height = u16::from(!self.headers.is_empty());
+        let hits = relevant_timeline()
+            .enumerate()
+            .flat_map(|(idx, (_, events))| {
+                let y = area.y + header_height + idx as u16 * ROW_HEIGHT;
+                events[columns.clone()]
+                    .iter()
+                    .zip(&column_areas)
+                    .filter_map(move |(id, column)| {
+                        let cell = Rect {
+                            y,
+                            height: ROW_HEIGHT,
+                            ..*column
+                        };
+                        Some((cell.intersection(area), (*id)?))
+                    })
+            })
+            .collect();
 
-        let rows = relevant_timeline
+        let rows = relevant_timeline()
             .map(|(timestamp, events)| {
-                iter::once(Cell::new(timestamp.format(DATETIME_FORMAT_LONG).unwrap())).chain(
+                iter::once(Cell::new(
+                    state
+                        .local(*timestamp)
+                        .format(DATETIME_FORMAT_LONG)
+                        .unwrap(),
+                ))
+                .chain(
                     // TODO: extract this into its own function
-                    events.iter().map(|id| {
+                    events[columns.clone()].iter().map(|id| {
                         let Some(id) = id else {
                             return Cell::new("");
                         };
 
-                        let text = state.schedule[id].title.as_str();
-                        let cell = Cell::new(wrap(text, cell_width as usize).collect::<Vec<_>>());
+                        let event = &state.schedule[id];
+                        let is_favourite = state.favourites.contains(id);
+                        let track_color = track_color(&state.schedule, &event.track);
+
+                        // leave some space for the marker and the track border
+                        let width = match is_favourite {
+                            true => cell_width.saturating_sub(2),
+                            false => cell_width,
+                        };
+                        let width = match track_color {
+                            Some(_) => width.saturating_sub(1),
+                            None => width,
+                        };
+                        let mut lines = wrap(&event.title, width as usize).collect::<Vec<_>>();
+                        if let (true, Some(first)) = (is_favourite, lines.first_mut()) {
+                            first.spans.insert(0, Span::raw(FAVOURITE_MARKER));
+                        }
+                        if let Some(color) = track_color {
+                            // the border should span the whole cell, even if the title is short
+                            lines.resize_with(usize::from(ROW_HEIGHT), Line::default);
+                            for line in &mut lines {
+                                line.spans.insert(0, Span::raw(TRACK_BORDER).fg(color));
+                            }
+                        }
+
+                        let cell = Cell::new(lines);
+                        let cell = match is_favourite {
+                            true => cell.style(Theme::current().favourite()),
+                            false => cell,
+                        };
                         if selected.id == *id {
                             cell.reversed()
                         } else {
@@ -123,92 +214,81 @@ impl ScheduleGrid {
                     }),
                 )
             })
-            .map(|cells| Row::new(cells).height(3));
+            .map(|cells| Row::new(cells).height(ROW_HEIGHT));
+
+        let position = format!(
+            "rooms {}–{} of {}",
+            columns.start + 1,
+            columns.end,
+            self.width()
+        );
+        let mut table = Table::new(rows, widths);
+        if !self.headers.is_empty() {
+            let header = iter::once(Cell::new(helper_span(&position))).chain(
+                self.headers[columns]
+                    .iter()
+                    .map(|name| Cell::new(Span::raw(name.as_str()).bold())),
+            );
+            table = table.header(Row::new(header));
+        }
 
         let mut table_state = TableState::new();
 
-        frame.render_stateful_widget(Table::new(rows, widths), frame.size(), &mut table_state);
-    }
-}
-
-/// [`Vec`], but fixed to a compile-time size and keeping elements at the same position regardless
-/// of elements removed in before.
-#[derive(Debug)]
-struct SlottedVec<T, const N: usize> {
-    data: [Option<T>; N],
-}
-
-impl<T, const N: usize> SlottedVec<T, N> {
-    /// [`None`] but const so it can be used as a copied expression in [`Default`].
-    const NONE: Option<T> = None;
-
-    fn new() -> Self {
-        Self::default()
-    }
-
-    /// Runs through all contained elements and removes them if the predicate returns [`false`].
-    fn retain(&mut self, mut predicate: impl FnMut(&mut T) -> bool) {
-        for slot in &mut self.data {
-            let Some(item) = slot else { continue };
+        frame.render_stateful_widget(table, area, &mut table_state);
 
-            if !(predicate)(item) {
-                *slot = Self::NONE;
-            }
-        }
+        hits
     }
+}
 
-    fn iter(&self) -> impl Iterator<Item = &Option<T>> {
-        self.data.iter()
-    }
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
 
-    fn iter_mut(&mut self) -> impl Iterator<Item = &mut Option<T>> {
-        self.data.iter_mut()
-    }
-}
+    use super::*;
+    use crate::{state::store::fixture::state, ui::View as _};
 
-impl<T, const N: usize> Default for SlottedVec<T, N> {
-    fn default() -> Self {
-        Self {
-            data: [Self::NONE; N],
-        }
+    /// Draws the grid onto a terminal of the given size, returning what ended up there.
+    fn draw(view: &mut View<'_>, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| view.draw(frame, frame.size()))
+            .unwrap();
+        terminal.backend().buffer().clone()
     }
-}
-
-impl<T, const N: usize> Extend<T> for SlottedVec<T, N> {
-    /// Try to fill the vector up from the start.
-    ///
-    /// Once the vector is all filled up, the iterator is dropped, without pulling remaining elements.
-    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        // a bit like k-merge
-        // we just iterate through both the vec and the iter
-        let mut iter = iter.into_iter();
-        let target = self.iter_mut();
-        for slot in target {
-            if slot.is_some() {
-                continue;
-            }
 
-            // oh noice, we have a free slot, let's fill it
-            let item = iter.next();
-            if item.is_none() {
-                // oh no the target iter is empty qwq
-                // technically we don't know if the iterator is fused
-                // so we could just continue here? idk
-                return;
-            }
-            *slot = item;
+    #[test]
+    fn clicks_select_the_event_drawn_there() {
+        let state = state("grid-clicks", &[]);
+        let mut view = View::new(&state);
+        let buffer = draw(&mut view, 120, 40);
+
+        let cells = view.cells.clone();
+        assert!(!cells.is_empty());
+        for (cell, id) in cells {
+            // the cell has to show the event it stands for, or clicks would hit the wrong one
+            let text: String = (cell.left()..cell.right())
+                .map(|x| buffer.get(x, cell.top()).symbol())
+                .collect();
+            let title = &state.schedule[&id].title;
+            let first_word = title.split_whitespace().next().unwrap();
+            assert!(text.contains(first_word), "{text:?} should show {title:?}");
+
+            let (column, row) = (cell.right() - 1, cell.bottom() - 1);
+            let action = view.click(column, row, false);
+            assert!(matches!(action, Some(Action::SelectEvent(clicked)) if clicked == id));
+            let action = view.click(column, row, true);
+            assert!(matches!(action, Some(Action::SwitchTo(Mode::Single))));
         }
     }
-}
 
-impl<T, const N: usize> FromIterator<Option<T>> for SlottedVec<T, N> {
-    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
-        let mut vec = Self::default();
-
-        for (slot, item) in vec.iter_mut().zip(iter) {
-            *slot = item;
-        }
+    #[test]
+    fn clicks_besides_events_do_nothing() {
+        let state = state("grid-misses", &[]);
+        let mut view = View::new(&state);
+        draw(&mut view, 120, 40);
 
-        vec
+        // the day tabs, and far below the last row
+        assert!(view.click(0, 0, false).is_none());
+        assert!(view.click(119, 39, true).is_none());
     }
 }
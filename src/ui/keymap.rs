@@ -0,0 +1,879 @@
+//! What pressing a key does, depending on the current [`Mode`].
+//!
+//! Keys are written like `q`, `N`, `enter`, `pagedown` or `ctrl-c`, and bound to commands like
+//! `quit` or `next-day`. See [`Command`] for all of them. Which keys are bound to begin with is
+//! decided by a [`Preset`], the user can bind more on top of that. Besides the modes, the
+//! overlays listing something have keys of their own, see [`Scope`].
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::{
+    state::{export, store::Mode},
+    Action, To, VerticalDirection,
+};
+
+/// Keys with a name instead of a character, as written in the config.
+const NAMED: [(&str, KeyCode); 16] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("space", KeyCode::Char(' ')),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 3] = [
+    ("ctrl", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+];
+
+/// One key together with the modifiers held while pressing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn char(ch: char) -> Self {
+        Self::new(KeyCode::Char(ch))
+    }
+
+    pub const fn ctrl(ch: char) -> Self {
+        Self {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub const fn alt(ch: char) -> Self {
+        Self {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::ALT,
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(event: KeyEvent) -> Self {
+        // shift is already part of the character, `N` is easier to write than `shift-n`
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+
+        // the key itself might be `-`, so only split off what are known modifiers
+        while let Some((modifier, key)) = rest.split_once('-') {
+            let Some((_, modifier)) = MODIFIERS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(modifier))
+            else {
+                break;
+            };
+            if key.is_empty() {
+                break;
+            }
+
+            modifiers |= *modifier;
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => NAMED
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+                .map(|(_, code)| *code)
+                .or_else(|| {
+                    let n = rest.strip_prefix(['f', 'F'])?.parse().ok()?;
+                    (1..=12).contains(&n).then_some(KeyCode::F(n))
+                })
+                .ok_or_else(|| format!("unknown key `{s}`"))?,
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}-")?;
+            }
+        }
+
+        if let Some((name, _)) = NAMED.iter().find(|(_, code)| *code == self.code) {
+            return f.write_str(name);
+        }
+        match self.code {
+            KeyCode::Char(ch) => write!(f, "{ch}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    Quit,
+    Left,
+    Right,
+    Up,
+    Down,
+    ScrollUp,
+    ScrollDown,
+    /// Look at the selected event in detail.
+    Open,
+    /// Go back to where the detail view was opened from, or to the grid from anywhere else.
+    Back,
+    Agenda,
+    Now,
+    Speakers,
+    /// Type what to narrow the speakers down to.
+    FilterSpeakers,
+    Favourite,
+    ToggleLayout,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Filter,
+    /// Filter by the value under the cursor of the filter picker, or stop doing so.
+    Toggle,
+    ClearFilter,
+    Tracks,
+    NextDay,
+    PreviousDay,
+    Reload,
+    ExportFavourites,
+    ExportFiltered,
+    /// List what each key does.
+    Help,
+    /// Open the link with this number in the detail view, counting from 1.
+    OpenLink(u8),
+}
+
+impl Command {
+    pub const ALL: [Self; 37] = [
+        Self::Quit,
+        Self::Left,
+        Self::Right,
+        Self::Up,
+        Self::Down,
+        Self::ScrollUp,
+        Self::ScrollDown,
+        Self::Open,
+        Self::Back,
+        Self::Agenda,
+        Self::Now,
+        Self::Speakers,
+        Self::FilterSpeakers,
+        Self::Favourite,
+        Self::ToggleLayout,
+        Self::Search,
+        Self::NextMatch,
+        Self::PreviousMatch,
+        Self::Filter,
+        Self::Toggle,
+        Self::ClearFilter,
+        Self::Tracks,
+        Self::NextDay,
+        Self::PreviousDay,
+        Self::Reload,
+        Self::ExportFavourites,
+        Self::ExportFiltered,
+        Self::Help,
+        Self::OpenLink(1),
+        Self::OpenLink(2),
+        Self::OpenLink(3),
+        Self::OpenLink(4),
+        Self::OpenLink(5),
+        Self::OpenLink(6),
+        Self::OpenLink(7),
+        Self::OpenLink(8),
+        Self::OpenLink(9),
+    ];
+
+    /// What the command is called, without the number for [`Command::OpenLink`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::ScrollUp => "scroll-up",
+            Self::ScrollDown => "scroll-down",
+            Self::Open => "open",
+            Self::Back => "back",
+            Self::Agenda => "agenda",
+            Self::Now => "now",
+            Self::Speakers => "speakers",
+            Self::FilterSpeakers => "filter-speakers",
+            Self::Favourite => "favourite",
+            Self::ToggleLayout => "toggle-layout",
+            Self::Search => "search",
+            Self::NextMatch => "next-match",
+            Self::PreviousMatch => "previous-match",
+            Self::Filter => "filter",
+            Self::Toggle => "toggle",
+            Self::ClearFilter => "clear-filter",
+            Self::Tracks => "tracks",
+            Self::NextDay => "next-day",
+            Self::PreviousDay => "previous-day",
+            Self::Reload => "reload",
+            Self::ExportFavourites => "export-favourites",
+            Self::ExportFiltered => "export-filtered",
+            Self::Help => "help",
+            Self::OpenLink(_) => "open-link",
+        }
+    }
+
+    /// What the command does, in a few words.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Left => "select the event to the left",
+            Self::Right => "select the event to the right",
+            Self::Up => "move the selection up",
+            Self::Down => "move the selection down",
+            Self::ScrollUp => "scroll up",
+            Self::ScrollDown => "scroll down",
+            Self::Open => "look at the selected event in detail",
+            Self::Back => "go back to the previous view",
+            Self::Agenda => "list starred events",
+            Self::Now => "show what's running right now",
+            Self::Speakers => "list everyone speaking and their events",
+            Self::FilterSpeakers => "narrow the speakers down by name",
+            Self::Favourite => "star or unstar the selected event",
+            Self::ToggleLayout => "switch between packed columns and one per room",
+            Self::Search => "search titles, descriptions and speakers",
+            Self::NextMatch => "select the next search match",
+            Self::PreviousMatch => "select the previous search match",
+            Self::Filter => "filter by track, room, language or type",
+            Self::Toggle => "filter by the value under the cursor or stop",
+            Self::ClearFilter => "remove all filters",
+            Self::Tracks => "show which colour belongs to which track",
+            Self::NextDay => "jump to the next conference day",
+            Self::PreviousDay => "jump to the previous conference day",
+            Self::Reload => "load the schedule again and show what changed",
+            Self::ExportFavourites => "export starred events into a calendar file",
+            Self::ExportFiltered => "export filtered events into a calendar file",
+            Self::Help => "list what each key does",
+            Self::OpenLink(_) => "open the link with that number",
+        }
+    }
+
+    /// Returns what to tell the state, or [`None`] if this command only opens an overlay or only
+    /// does something inside one.
+    pub fn action(self) -> Option<Action> {
+        let action = match self {
+            Self::Quit => Action::Exit,
+            Self::Left => Action::Select(To::Left),
+            Self::Right => Action::Select(To::Right),
+            Self::Up => Action::Select(To::Up),
+            Self::Down => Action::Select(To::Below),
+            Self::ScrollUp => Action::Scroll(VerticalDirection::Up),
+            Self::ScrollDown => Action::Scroll(VerticalDirection::Down),
+            Self::Open => Action::SwitchTo(Mode::Single),
+            Self::Back => Action::Back,
+            Self::Agenda => Action::SwitchTo(Mode::Agenda),
+            Self::Now => Action::SwitchTo(Mode::Now),
+            Self::Speakers => Action::SwitchTo(Mode::Speakers),
+            Self::Favourite => Action::ToggleFavourite,
+            Self::ToggleLayout => Action::ToggleGridLayout,
+            Self::NextMatch => Action::CycleMatch(VerticalDirection::Down),
+            Self::PreviousMatch => Action::CycleMatch(VerticalDirection::Up),
+            Self::NextDay => Action::JumpDay(VerticalDirection::Down),
+            Self::PreviousDay => Action::JumpDay(VerticalDirection::Up),
+            Self::Reload => Action::Reload { quiet: false },
+            Self::ExportFavourites => Action::Export(export::Scope::Favourites),
+            Self::ExportFiltered => Action::Export(export::Scope::Filtered),
+            Self::ClearFilter => Action::ClearFilter,
+            Self::OpenLink(n) => Action::OpenLink(n.into()),
+            Self::Search
+            | Self::FilterSpeakers
+            | Self::Filter
+            | Self::Toggle
+            | Self::Tracks
+            | Self::Help => return None,
+        };
+        Some(action)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OpenLink(n) => write!(f, "{}-{n}", self.name()),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown command `{s}`"))
+    }
+}
+
+/// Where a key can be bound on its own, instead of everywhere at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Mode(Mode),
+    /// The overlays listing something, like the filter picker or the help. The prompts take all
+    /// keys as text instead.
+    Overlay,
+}
+
+impl From<Mode> for Scope {
+    fn from(mode: Mode) -> Self {
+        Self::Mode(mode)
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    /// Accepts `overlay` or the name of a mode.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.eq_ignore_ascii_case("overlay") {
+            true => Ok(Self::Overlay),
+            false => s
+                .parse()
+                .map(Self::Mode)
+                .map_err(|err| format!("{err} or overlay")),
+        }
+    }
+}
+
+/// One key bound to one command, as given by the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Binding {
+    /// Only there, or everywhere if [`None`].
+    pub scope: Option<Scope>,
+    pub chord: KeyChord,
+    pub command: Command,
+}
+
+/// Which keys are bound to begin with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// `h`, `j`, `k` and `l` to move around, as well as the arrow keys.
+    #[default]
+    Vim,
+    /// `ctrl-b`, `ctrl-n`, `ctrl-p` and `ctrl-f` to move around, as well as the arrow keys.
+    Emacs,
+    /// Only the arrow keys to move around.
+    Arrows,
+}
+
+impl Preset {
+    fn bindings(self) -> Vec<Binding> {
+        let global = |chord, command| Binding {
+            scope: None,
+            chord,
+            command,
+        };
+        let arrows = [
+            global(KeyChord::new(KeyCode::Left), Command::Left),
+            global(KeyChord::new(KeyCode::Right), Command::Right),
+        ];
+
+        let mut bindings: Vec<_> = [
+            (KeyChord::char('f'), Command::Favourite),
+            (KeyChord::char('a'), Command::Agenda),
+            (KeyChord::char('t'), Command::Now),
+            (KeyChord::char('s'), Command::Speakers),
+            (KeyChord::char('/'), Command::Search),
+            (KeyChord::char('F'), Command::Filter),
+            (KeyChord::char('T'), Command::Tracks),
+            (KeyChord::char('n'), Command::NextMatch),
+            (KeyChord::char('N'), Command::PreviousMatch),
+            (KeyChord::char(']'), Command::NextDay),
+            (KeyChord::char('['), Command::PreviousDay),
+            (KeyChord::char('R'), Command::Reload),
+            (KeyChord::char('x'), Command::ExportFavourites),
+            (KeyChord::char('X'), Command::ExportFiltered),
+            (KeyChord::char('q'), Command::Quit),
+            (KeyChord::char('?'), Command::Help),
+        ]
+        .into_iter()
+        .map(|(chord, command)| global(chord, command))
+        .chain(arrows)
+        .collect();
+
+        match self {
+            Self::Vim => bindings.extend([
+                global(KeyChord::char('h'), Command::Left),
+                global(KeyChord::char('l'), Command::Right),
+            ]),
+            Self::Emacs => bindings.extend([
+                global(KeyChord::ctrl('b'), Command::Left),
+                global(KeyChord::ctrl('f'), Command::Right),
+                global(KeyChord::ctrl('g'), Command::Back),
+                global(KeyChord::ctrl('s'), Command::Search),
+            ]),
+            Self::Arrows => (),
+        }
+
+        // mode-specific ones come last so the global ones above don't override them
+        bindings.extend(vertical(
+            KeyChord::new(KeyCode::Up),
+            KeyChord::new(KeyCode::Down),
+        ));
+        match self {
+            Self::Vim => bindings.extend(vertical(KeyChord::char('k'), KeyChord::char('j'))),
+            Self::Emacs => {
+                bindings.extend(vertical(KeyChord::ctrl('p'), KeyChord::ctrl('n')));
+                for mode in [Mode::Single, Mode::Changes] {
+                    bindings.extend([
+                        Binding {
+                            scope: Some(mode.into()),
+                            chord: KeyChord::alt('v'),
+                            command: Command::ScrollUp,
+                        },
+                        Binding {
+                            scope: Some(mode.into()),
+                            chord: KeyChord::ctrl('v'),
+                            command: Command::ScrollDown,
+                        },
+                    ]);
+                }
+            }
+            Self::Arrows => (),
+        }
+
+        let enter = KeyChord::new(KeyCode::Enter);
+        let esc = KeyChord::new(KeyCode::Esc);
+        let per_mode = [
+            (Mode::Grid, enter, Command::Open),
+            (Mode::Grid, KeyChord::char('r'), Command::ToggleLayout),
+            (Mode::Single, enter, Command::Back),
+            (Mode::Single, esc, Command::Back),
+            (Mode::Agenda, enter, Command::Open),
+            (Mode::Agenda, esc, Command::Back),
+            (Mode::Search, enter, Command::Open),
+            (Mode::Search, esc, Command::Back),
+            (Mode::Changes, enter, Command::Back),
+            (Mode::Changes, esc, Command::Back),
+            (Mode::Now, esc, Command::Back),
+            (Mode::Speakers, enter, Command::Open),
+            (Mode::Speakers, esc, Command::Back),
+            (Mode::Speakers, KeyChord::char('/'), Command::FilterSpeakers),
+        ];
+        bindings.extend(per_mode.map(|(mode, chord, command)| Binding {
+            scope: Some(mode.into()),
+            chord,
+            command,
+        }));
+
+        // closing an overlay also works with the key that opened it, or the one for quitting
+        let overlay = [
+            (enter, Command::Toggle),
+            (KeyChord::char(' '), Command::Toggle),
+            (KeyChord::char('c'), Command::ClearFilter),
+            (esc, Command::Back),
+        ];
+        bindings.extend(overlay.map(|(chord, command)| Binding {
+            scope: Some(Scope::Overlay),
+            chord,
+            command,
+        }));
+
+        // the detail view numbers its links, so the digits open them
+        bindings.extend((1..=9).map(|n| Binding {
+            scope: Some(Mode::Single.into()),
+            chord: KeyChord::char(char::from(b'0' + n)),
+            command: Command::OpenLink(n),
+        }));
+
+        bindings
+    }
+}
+
+/// Binds the keys to moving up and down, which means scrolling where there's text to scroll
+/// through, and moving the selection or cursor everywhere else.
+fn vertical(up: KeyChord, down: KeyChord) -> Vec<Binding> {
+    Mode::ALL
+        .into_iter()
+        .map(Scope::Mode)
+        .chain([Scope::Overlay])
+        .filter_map(|scope| {
+            let (up_command, down_command) = match scope {
+                Scope::Mode(Mode::Grid | Mode::Agenda | Mode::Search | Mode::Speakers)
+                | Scope::Overlay => (Command::Up, Command::Down),
+                Scope::Mode(Mode::Single | Mode::Changes) => {
+                    (Command::ScrollUp, Command::ScrollDown)
+                }
+                Scope::Mode(Mode::Now) => return None,
+            };
+            Some(
+                [(up, up_command), (down, down_command)].map(|(chord, command)| Binding {
+                    scope: Some(scope),
+                    chord,
+                    command,
+                }),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// All keys bound, both by the preset and the user.
+#[derive(Clone, Debug, Default)]
+pub struct Keymap {
+    /// Apply everywhere, unless the scope binds the key on its own.
+    global: HashMap<KeyChord, Command>,
+    scopes: HashMap<Scope, HashMap<KeyChord, Command>>,
+}
+
+impl Keymap {
+    /// Starts out with the bindings of the preset, then adds the given ones on top.
+    pub fn new(preset: Preset, bindings: &[Binding]) -> Self {
+        let mut keymap = Self::default();
+        for binding in preset.bindings().iter().chain(bindings) {
+            keymap.bind(*binding);
+        }
+        keymap
+    }
+
+    /// Binds the key, replacing whatever it was bound to before. A global binding replaces
+    /// scoped ones, too.
+    pub fn bind(&mut self, binding: Binding) {
+        match binding.scope {
+            Some(scope) => {
+                self.scopes
+                    .entry(scope)
+                    .or_default()
+                    .insert(binding.chord, binding.command);
+            }
+            None => {
+                for bindings in self.scopes.values_mut() {
+                    bindings.remove(&binding.chord);
+                }
+                self.global.insert(binding.chord, binding.command);
+            }
+        }
+    }
+
+    /// Returns what the key does in the given mode or overlays, if anything.
+    pub fn get(&self, scope: impl Into<Scope>, chord: KeyChord) -> Option<Command> {
+        self.scopes
+            .get(&scope.into())
+            .and_then(|bindings| bindings.get(&chord))
+            .or_else(|| self.global.get(&chord))
+            .copied()
+    }
+
+    /// Lists which keys each command is bound to in the given mode or overlays, in the order of
+    /// [`Command::ALL`]. Commands without any key are left out, and ones only differing in what
+    /// they apply to, like the [`Command::OpenLink`] ones, are merged into the first of them.
+    pub fn bindings(&self, scope: impl Into<Scope>) -> Vec<(Command, Vec<KeyChord>)> {
+        let scope = scope.into();
+        let scope_chords = self
+            .scopes
+            .get(&scope)
+            .into_iter()
+            .flat_map(|bindings| bindings.keys());
+        let mut chords: Vec<KeyChord> = self.global.keys().chain(scope_chords).copied().collect();
+        // plain characters are the most likely to be remembered, so they go first
+        chords.sort_by_key(|chord| {
+            (
+                chord.modifiers.bits(),
+                !matches!(chord.code, KeyCode::Char(_)),
+                chord.to_string(),
+            )
+        });
+        chords.dedup();
+
+        let mut listed: Vec<(Command, Vec<KeyChord>)> = Vec::new();
+        for command in Command::ALL {
+            let bound: Vec<_> = chords
+                .iter()
+                .filter(|chord| self.get(scope, **chord) == Some(command))
+                .copied()
+                .collect();
+            if bound.is_empty() {
+                continue;
+            }
+
+            match listed.last_mut() {
+                Some((previous, chords)) if previous.name() == command.name() => {
+                    chords.extend(bound)
+                }
+                _ => listed.push((command, bound)),
+            }
+        }
+        listed
+    }
+
+    /// Returns the key listed first for the command by [`Keymap::bindings`], if it's bound at all.
+    pub fn first_key(&self, scope: impl Into<Scope>, command: Command) -> Option<KeyChord> {
+        self.bindings(scope)
+            .into_iter()
+            .find(|(bound, _)| *bound == command)
+            .and_then(|(_, chords)| chords.first().copied())
+    }
+
+    /// Describes the commands in one line like `space toggle · esc close`, naming the first key of
+    /// each. Commands sharing a label are named together, as in `j/k scroll`. Ones without any key
+    /// are left out.
+    pub fn hint(&self, scope: impl Into<Scope>, entries: &[(&[Command], &str)]) -> String {
+        let scope = scope.into();
+        entries
+            .iter()
+            .filter_map(|(commands, label)| {
+                let keys: Vec<_> = commands
+                    .iter()
+                    .filter_map(|command| self.first_key(scope, *command))
+                    .map(|chord| chord.to_string())
+                    .collect();
+                (!keys.is_empty()).then(|| format!("{} {label}", keys.join("/")))
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTER: KeyChord = KeyChord::new(KeyCode::Enter);
+
+    fn binding(mode: Option<Mode>, chord: KeyChord, command: Command) -> Binding {
+        Binding {
+            scope: mode.map(Scope::Mode),
+            chord,
+            command,
+        }
+    }
+
+    #[test]
+    fn chords_round_trip() {
+        for chord in [
+            "q", "N", "-", "ctrl-c", "ctrl--", "alt-v", "enter", "pagedown", "f5",
+        ] {
+            let parsed: KeyChord = chord.parse().unwrap();
+            assert_eq!(parsed.to_string(), chord);
+        }
+
+        assert_eq!("ctrl--".parse(), Ok(KeyChord::ctrl('-')));
+        assert!("hyper-x".parse::<KeyChord>().is_err());
+        assert!("f13".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn shift_is_part_of_the_character() {
+        let event = KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(event), KeyChord::char('N'));
+
+        let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(event).modifiers, KeyModifiers::SHIFT);
+    }
+
+    #[test]
+    fn mode_bindings_of_presets_beat_global_ones() {
+        let keymap = Keymap::new(Preset::Vim, &[]);
+
+        assert_eq!(
+            keymap.get(Mode::Grid, KeyChord::char('/')),
+            Some(Command::Search)
+        );
+        assert_eq!(
+            keymap.get(Mode::Speakers, KeyChord::char('/')),
+            Some(Command::FilterSpeakers)
+        );
+        assert_eq!(
+            keymap.get(Mode::Grid, KeyChord::char('j')),
+            Some(Command::Down)
+        );
+        assert_eq!(
+            keymap.get(Mode::Single, KeyChord::char('j')),
+            Some(Command::ScrollDown)
+        );
+        assert_eq!(keymap.get(Mode::Now, KeyChord::char('j')), None);
+        assert_eq!(keymap.get(Mode::Grid, ENTER), Some(Command::Open));
+        assert_eq!(keymap.get(Mode::Single, ENTER), Some(Command::Back));
+    }
+
+    #[test]
+    fn presets_differ_in_movement() {
+        let vim = Keymap::new(Preset::Vim, &[]);
+        let emacs = Keymap::new(Preset::Emacs, &[]);
+        let arrows = Keymap::new(Preset::Arrows, &[]);
+        let left = KeyChord::new(KeyCode::Left);
+
+        for keymap in [&vim, &emacs, &arrows] {
+            assert_eq!(keymap.get(Mode::Grid, left), Some(Command::Left));
+        }
+        assert_eq!(
+            vim.get(Mode::Grid, KeyChord::char('h')),
+            Some(Command::Left)
+        );
+        assert_eq!(arrows.get(Mode::Grid, KeyChord::char('h')), None);
+        assert_eq!(
+            emacs.get(Mode::Grid, KeyChord::ctrl('n')),
+            Some(Command::Down)
+        );
+        assert_eq!(
+            emacs.get(Mode::Single, KeyChord::ctrl('v')),
+            Some(Command::ScrollDown)
+        );
+        assert_eq!(emacs.get(Mode::Grid, KeyChord::ctrl('v')), None);
+    }
+
+    #[test]
+    fn global_binding_replaces_mode_bindings() {
+        let keymap = Keymap::new(Preset::Vim, &[binding(None, ENTER, Command::Favourite)]);
+
+        for mode in Mode::ALL {
+            assert_eq!(keymap.get(mode, ENTER), Some(Command::Favourite));
+        }
+    }
+
+    #[test]
+    fn mode_binding_only_applies_in_its_mode() {
+        let q = KeyChord::char('q');
+        let keymap = Keymap::new(
+            Preset::Vim,
+            &[binding(Some(Mode::Agenda), q, Command::Back)],
+        );
+
+        assert_eq!(keymap.get(Mode::Agenda, q), Some(Command::Back));
+        assert_eq!(keymap.get(Mode::Grid, q), Some(Command::Quit));
+    }
+
+    #[test]
+    fn later_bindings_win() {
+        let x = KeyChord::char('x');
+
+        let keymap = Keymap::new(
+            Preset::Arrows,
+            &[
+                binding(Some(Mode::Grid), x, Command::Reload),
+                binding(None, x, Command::Quit),
+            ],
+        );
+        assert_eq!(keymap.get(Mode::Grid, x), Some(Command::Quit));
+
+        let keymap = Keymap::new(
+            Preset::Arrows,
+            &[
+                binding(None, x, Command::Quit),
+                binding(Some(Mode::Grid), x, Command::Reload),
+            ],
+        );
+        assert_eq!(keymap.get(Mode::Grid, x), Some(Command::Reload));
+        assert_eq!(keymap.get(Mode::Single, x), Some(Command::Quit));
+    }
+
+    #[test]
+    fn listing_merges_link_commands() {
+        let listed = Keymap::new(Preset::Vim, &[]).bindings(Mode::Single);
+
+        let (command, chords) = listed
+            .iter()
+            .find(|(command, _)| command.name() == "open-link")
+            .unwrap();
+        assert_eq!(*command, Command::OpenLink(1));
+        assert_eq!(chords.len(), 9);
+
+        let (_, chords) = listed
+            .iter()
+            .find(|(command, _)| *command == Command::Back)
+            .unwrap();
+        assert!(chords.contains(&ENTER));
+        assert!(chords.contains(&KeyChord::new(KeyCode::Esc)));
+    }
+
+    #[test]
+    fn overlays_have_their_own_keys() {
+        let keymap = Keymap::new(Preset::Vim, &[]);
+
+        assert_eq!(
+            keymap.get(Scope::Overlay, KeyChord::char('j')),
+            Some(Command::Down)
+        );
+        assert_eq!(keymap.get(Scope::Overlay, ENTER), Some(Command::Toggle));
+        assert_eq!(keymap.get(Mode::Grid, ENTER), Some(Command::Open));
+        // global ones still apply, so the overlays can be closed with them
+        assert_eq!(
+            keymap.get(Scope::Overlay, KeyChord::char('q')),
+            Some(Command::Quit)
+        );
+
+        assert_eq!("overlay".parse(), Ok(Scope::Overlay));
+        assert_eq!("single".parse(), Ok(Scope::Mode(Mode::Single)));
+        assert!("nowhere".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn hints_name_the_first_key_of_each_command() {
+        let entries: &[(&[Command], &str)] = &[
+            (&[Command::Down, Command::Up], "scroll"),
+            (&[Command::OpenLink(1)], "open"),
+            (&[Command::Back], "close"),
+        ];
+
+        let vim = Keymap::new(
+            Preset::Vim,
+            &[binding(None, KeyChord::char('z'), Command::Back)],
+        );
+        assert_eq!(vim.hint(Scope::Overlay, entries), "j/k scroll · z close");
+        let arrows = Keymap::new(Preset::Arrows, &[]);
+        assert_eq!(
+            arrows.hint(Scope::Overlay, entries),
+            "down/up scroll · esc close"
+        );
+        assert_eq!(
+            arrows.first_key(Mode::Grid, Command::Favourite),
+            Some(KeyChord::char('f'))
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Drawing abstracts and descriptions with their emphasis, lists and links, wrapped to a width.
+
+use std::ops::Range;
+
+use ratatui::prelude::*;
+use textwrap::{
+    core::{break_words, display_width},
+    word_splitters::split_words,
+    WordSeparator, WrapAlgorithm,
+};
+
+use crate::state::schedule::markup::{self, BlockKind, Document};
+
+use super::color::Theme;
+
+/// The document as lines, together with where the links in there ended up.
+pub struct Rendered {
+    pub lines: Vec<Line<'static>>,
+    /// Line index, columns and target of each link.
+    pub links: Vec<(usize, Range<usize>, String)>,
+}
+
+/// One piece of text of a line before wrapping, all styled the same.
+struct Piece {
+    text: String,
+    style: Style,
+    link: Option<String>,
+}
+
+/// Draws the document into lines at most `width` wide. Links are followed by the number
+/// `number` returns for them, if any.
+pub fn render(
+    document: &Document,
+    width: usize,
+    number: impl Fn(&str) -> Option<usize>,
+) -> Rendered {
+    let mut rendered = Rendered {
+        lines: Vec::new(),
+        links: Vec::new(),
+    };
+    let splitter = super::word_splitter();
+    let mut previous_kind = None;
+
+    for block in &document.blocks {
+        // list items are close together, everything else is set apart by an empty line
+        let in_list = matches!(
+            (&previous_kind, &block.kind),
+            (Some(BlockKind::ListItem { .. }), BlockKind::ListItem { .. })
+        );
+        if previous_kind.is_some() && !in_list {
+            rendered.lines.push(Line::default());
+        }
+        previous_kind = Some(block.kind.clone());
+
+        if block.kind == BlockKind::Code {
+            let code: String = block.spans.iter().map(|span| span.text.as_str()).collect();
+            rendered.lines.extend(
+                code.split('\n')
+                    .map(|line| Line::styled(format!("  {line}"), Theme::current().code())),
+            );
+            continue;
+        }
+
+        let (first_prefix, prefix, base) = match &block.kind {
+            BlockKind::Heading(level) => {
+                let style = match level {
+                    1 => Style::new().bold().underlined(),
+                    _ => Style::new().bold(),
+                };
+                (String::new(), String::new(), style)
+            }
+            BlockKind::ListItem { depth, marker } => {
+                let first = format!("{}{marker} ", "  ".repeat(*depth));
+                let rest = " ".repeat(display_width(&first));
+                (first, rest, Style::new())
+            }
+            BlockKind::Quote => ("│ ".to_string(), "│ ".to_string(), Style::new().italic()),
+            BlockKind::Paragraph | BlockKind::Code => (String::new(), String::new(), Style::new()),
+        };
+        let prefix_style = match block.kind {
+            BlockKind::Quote => Theme::current().helper(),
+            _ => Style::new(),
+        };
+
+        let mut pieces = Vec::new();
+        for (idx, span) in block.spans.iter().enumerate() {
+            pieces.push(Piece {
+                text: span.text.clone(),
+                style: style(span.style, span.link.is_some(), base),
+                link: span.link.clone(),
+            });
+
+            // the number goes right after the link, so it's clear where to type
+            let link_ends = match block.spans.get(idx + 1) {
+                Some(next) => next.link != span.link,
+                None => true,
+            };
+            if let Some(n) = span.link.as_deref().and_then(&number).filter(|_| link_ends) {
+                pieces.push(Piece {
+                    text: format!("[{n}]"),
+                    style: Theme::current().helper(),
+                    link: None,
+                });
+            }
+        }
+
+        let available = width.saturating_sub(display_width(&first_prefix)).max(1);
+        let mut first = true;
+        for line in hard_lines(pieces) {
+            for row in wrap(&line, available, &splitter) {
+                let prefix = match first {
+                    true => &first_prefix,
+                    false => &prefix,
+                };
+                first = false;
+
+                let offset = display_width(prefix);
+                let mut spans = vec![Span::styled(prefix.clone(), prefix_style)];
+                let mut column = offset;
+                for piece in row {
+                    let piece_width = display_width(&piece.text);
+                    if let Some(url) = piece.link {
+                        let line_idx = rendered.lines.len();
+                        match rendered.links.last_mut() {
+                            // a link might be split up into multiple pieces
+                            Some((last_line, columns, last_url))
+                                if *last_line == line_idx
+                                    && columns.end == column
+                                    && *last_url == url =>
+                            {
+                                columns.end += piece_width
+                            }
+                            _ => rendered
+                                .links
+                                .push((line_idx, column..column + piece_width, url)),
+                        }
+                    }
+                    column += piece_width;
+                    spans.push(Span::styled(piece.text, piece.style));
+                }
+                rendered.lines.push(Line::from(spans));
+            }
+        }
+    }
+
+    rendered
+}
+
+fn style(style: markup::Style, link: bool, base: Style) -> Style {
+    let mut result = base;
+    if style.bold {
+        result = result.bold();
+    }
+    if style.italic {
+        result = result.italic();
+    }
+    if style.code {
+        result = result.patch(Theme::current().code());
+    }
+    if link {
+        result = result.underlined();
+    }
+    result
+}
+
+/// Splits the pieces at every line break.
+fn hard_lines(pieces: Vec<Piece>) -> Vec<Vec<Piece>> {
+    let mut lines = vec![Vec::new()];
+    for piece in pieces {
+        let mut parts = piece.text.split('\n');
+        if let Some(part) = parts.next() {
+            lines.last_mut().unwrap().push(Piece {
+                text: part.to_string(),
+                style: piece.style,
+                link: piece.link.clone(),
+            });
+        }
+        for part in parts {
+            lines.push(vec![Piece {
+                text: part.to_string(),
+                style: piece.style,
+                link: piece.link.clone(),
+            }]);
+        }
+    }
+    lines
+}
+
+/// Wraps one line with hyphenation like [`super::wrap`] does, but keeps the style of each piece.
+fn wrap(pieces: &[Piece], width: usize, splitter: &textwrap::WordSplitter) -> Vec<Vec<Piece>> {
+    let line: String = pieces.iter().map(|piece| piece.text.as_str()).collect();
+    if line.trim().is_empty() {
+        return vec![Vec::new()];
+    }
+
+    // where each piece is in the line, to look up which style the wrapped bits have
+    let mut ranges = Vec::with_capacity(pieces.len());
+    let mut start = 0;
+    for piece in pieces {
+        ranges.push((start..start + piece.text.len(), piece));
+        start += piece.text.len();
+    }
+
+    // all the same as `textwrap::wrap` does, just without turning it into a string at the end
+    let words = WordSeparator::new().find_words(&line);
+    let words = split_words(words, splitter);
+    let words = break_words(words, width);
+    let widths = [width];
+    let rows = WrapAlgorithm::new().wrap(&words, &widths);
+
+    // textwrap relies on the words being contiguous, so it's fine to do the same here
+    let mut offset = 0;
+    let mut wrapped = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut result = Vec::new();
+        for (idx, word) in row.iter().enumerate() {
+            let last = idx + 1 == row.len();
+            let end = match last {
+                true => offset + word.len(),
+                false => offset + word.len() + word.whitespace.len(),
+            };
+            slice(&line, &ranges, offset..end, &mut result);
+
+            if last && !word.penalty.is_empty() {
+                let (_, piece) = ranges
+                    .iter()
+                    .find(|(range, _)| range.contains(&(offset + word.len()).saturating_sub(1)))
+                    .expect("word to be inside the line");
+                result.push(Piece {
+                    text: word.penalty.to_string(),
+                    style: piece.style,
+                    link: piece.link.clone(),
+                });
+            }
+
+            offset += word.len() + word.whitespace.len();
+        }
+        wrapped.push(result);
+    }
+    wrapped
+}
+
+/// Cuts out `range` of the line, split into pieces wherever the style changes.
+fn slice(
+    line: &str,
+    ranges: &[(Range<usize>, &Piece)],
+    range: Range<usize>,
+    into: &mut Vec<Piece>,
+) {
+    for (piece_range, piece) in ranges {
+        let start = piece_range.start.max(range.start);
+        let end = piece_range.end.min(range.end);
+        if start >= end {
+            continue;
+        }
+
+        into.push(Piece {
+            text: line[start..end].to_string(),
+            style: piece.style,
+            link: piece.link.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use textwrap::WordSplitter;
+
+    use super::*;
+
+    fn piece(text: &str, style: Style) -> Piece {
+        Piece {
+            text: text.to_string(),
+            style,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn hyphenated_words_keep_their_styles() {
+        // splitting in the middle like a dictionary would, without depending on one
+        let splitter = WordSplitter::Custom(|word| match word {
+            "wrapping" => vec![4],
+            _ => Vec::new(),
+        });
+        let bold = Style::new().bold();
+        let italic = Style::new().italic();
+        let pieces = [
+            piece("plaïn ", Style::new()),
+            piece("wrap", bold),
+            piece("ping", italic),
+        ];
+
+        let rows: Vec<Vec<_>> = wrap(&pieces, 11, &splitter)
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|piece| (piece.text, piece.style))
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            rows,
+            [
+                vec![
+                    ("plaïn ".to_string(), Style::new()),
+                    ("wrap".to_string(), bold),
+                    ("-".to_string(), bold),
+                ],
+                vec![("ping".to_string(), italic)],
+            ]
+        );
+    }
+}
@@ -9,64 +9,103 @@
 //!
 //! See the [`crate`] module documentation for details.
 
+mod agenda;
+mod changes;
+mod color;
 mod grid;
+pub mod keymap;
+mod markup;
+mod now;
+mod overlay;
+mod search;
 mod single;
+mod speakers;
+
+pub use color::Theme;
 
 use std::{
     io::{stdout, Stdout},
     panic,
-    time::Duration,
+    rc::Rc,
+    time::{Duration, Instant},
 };
 
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, MouseEvent,
-        MouseEventKind,
+        self, DisableMouseCapture, EnableMouseCapture, KeyEvent, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
     },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use eyre::Result;
 use hyphenation::{Language, Load, Standard};
-use ratatui::prelude::*;
+use ratatui::{prelude::*, widgets::Paragraph};
 use textwrap::{Options, WordSplitter};
 use time::{format_description::FormatItem, macros::format_description};
 
+use keymap::{Command, Keymap};
+use overlay::Overlay;
+
 use crate::{
+    config::Config,
     state::store::{Mode, State},
-    Action, To, VerticalDirection,
+    Action,
 };
 
 /// Implementation of viewing a specific [`Mode`]. Created for one frame, then destroyed again.
 pub trait View {
-    /// Draw this mode in all detail.
-    fn draw(&mut self, frame: &mut Frame<'_>);
+    /// Draw this mode in all detail into the given area, which leaves out the header and status
+    /// bar.
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect);
 
-    /// Process mode-specific input [`TerminalEvent`]s.
-    fn process(&mut self, _event: TerminalEvent) -> Option<Action> {
+    /// React to a left click at the given terminal cell, using what was drawn last. `double` is
+    /// set if it's the second click at the same cell in short succession.
+    fn click(&mut self, _column: u16, _row: u16, _double: bool) -> Option<Action> {
         None
     }
 }
 
-fn map_mode_to_view<'state>(state: &'state State) -> Box<dyn View + 'state> {
+fn map_mode_to_view<'state>(
+    state: &'state State,
+    keymap: &'state Keymap,
+) -> Box<dyn View + 'state> {
     // could be facilitated with a macro if the manual matching becomes too repetetive
     match state.mode {
-        Mode::Grid => Box::new(grid::View { state }),
-        Mode::Single => Box::new(single::View { state }),
+        Mode::Grid => Box::new(grid::View::new(state)),
+        Mode::Single => Box::new(single::View::new(state)),
+        Mode::Agenda => Box::new(agenda::View { state, keymap }),
+        Mode::Search => Box::new(search::View { state }),
+        Mode::Changes => Box::new(changes::View { state }),
+        Mode::Now => Box::new(now::View { state }),
+        Mode::Speakers => Box::new(speakers::View::new(state)),
     }
 }
 
 pub struct Ui {
     terminal: Terminal<CrosstermBackend<Stdout>>,
+    /// Drawn over the view and receiving all input, if open.
+    overlay: Option<Overlay>,
+    /// What each key does in each mode. Shared, so views can borrow it while the UI changes.
+    keymap: Rc<Keymap>,
+    /// When and where the left mouse button was last pressed, for telling double-clicks apart.
+    last_click: Option<(Instant, u16, u16)>,
 }
 
 impl Ui {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: &Config) -> Result<Self> {
+        config.theme.unwrap_or_default().install();
+
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
         init_terminal(&mut terminal)?;
         install_panic_hook();
 
-        Ok(Self { terminal })
+        Ok(Self {
+            terminal,
+            overlay: None,
+            keymap: Rc::new(Keymap::new(config.keymap.unwrap_or_default(), &config.keys)),
+            last_click: None,
+        })
     }
 
     pub fn clean_up(self) -> Result<()> {
@@ -75,51 +114,179 @@ impl Ui {
     }
 
     pub fn frame(&mut self, state: &State) -> Result<Option<Action>> {
-        let mut view = map_mode_to_view(state);
-        self.draw(&mut view)?;
-        self.input(&mut view)
+        let keymap = Rc::clone(&self.keymap);
+        let mut view = map_mode_to_view(state, &keymap);
+        self.draw(state, &mut view)?;
+        self.input(state, &mut view)
     }
 
-    fn draw<'state>(&mut self, view: &mut Box<dyn View + 'state>) -> Result<()> {
-        self.terminal.draw(|frame| view.draw(frame))?;
+    fn draw<'state>(&mut self, state: &State, view: &mut Box<dyn View + 'state>) -> Result<()> {
+        let overlay = &self.overlay;
+        let keymap = &self.keymap;
+        self.terminal.draw(|frame| {
+            let layout = Layout::default()
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ])
+                .split(frame.size());
+
+            draw_header(state, layout[0], frame);
+            view.draw(frame, layout[1]);
+            draw_status(state, layout[2], frame);
+
+            if let Some(overlay) = overlay {
+                overlay.draw(state, keymap, frame);
+            }
+        })?;
         Ok(())
     }
 
-    fn input<'state>(&mut self, view: &mut Box<dyn View + 'state>) -> Result<Option<Action>> {
+    fn input<'state>(
+        &mut self,
+        state: &State,
+        view: &mut Box<dyn View + 'state>,
+    ) -> Result<Option<Action>> {
         const FRAME_DURATION: Duration = Duration::from_millis(16);
+        // how quickly the second click needs to follow the first one
+        const DOUBLE_CLICK: Duration = Duration::from_millis(400);
 
         if !event::poll(FRAME_DURATION)? {
             return Ok(None);
         }
         let event = event::read()?;
 
-        // try to match against "well-known" ones first
+        // overlays are modal, so they get everything while open
+        if let Some(overlay) = self.overlay.take() {
+            let (overlay, action) = overlay.process(state, &self.keymap, event);
+            self.overlay = overlay;
+            return Ok(action);
+        }
+
+        // the keymap knows about all modes
         // so each one doesn't have to handle scrolling again, for example
 
-        let mut forward = |event| Ok(view.process(event));
-
-        let action = match event {
-            // TODO: should actually go to the grid view later on
-            TerminalEvent::Key(KeyEvent {
-                kind: KeyEventKind::Press,
-                code: KeyCode::Char(ch),
-                ..
-            }) => match ch {
-                'h' => Action::Select(To::Left),
-                'l' => Action::Select(To::Right),
-                'q' => Action::Exit,
-                _ => return forward(event),
+        if let TerminalEvent::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            ..
+        }) = event
+        {
+            let now = Instant::now();
+            let double = self.last_click.is_some_and(|(at, last_column, last_row)| {
+                now - at < DOUBLE_CLICK && (last_column, last_row) == (column, row)
+            });
+            // a triple-click shouldn't count as two double-clicks
+            self.last_click = (!double).then_some((now, column, row));
+
+            return Ok(view.click(column, row, double));
+        }
+
+        let command = match event {
+            TerminalEvent::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Press,
+                    ..
+                },
+            ) => match self.keymap.get(state.mode, key.into()) {
+                Some(command) => command,
+                None => return Ok(None),
             },
             TerminalEvent::Mouse(MouseEvent { kind, .. }) => match kind {
-                MouseEventKind::ScrollUp => Action::Scroll(VerticalDirection::Up),
-                MouseEventKind::ScrollDown => Action::Scroll(VerticalDirection::Down),
-                _ => return forward(event),
+                MouseEventKind::ScrollUp => Command::ScrollUp,
+                MouseEventKind::ScrollDown => Command::ScrollDown,
+                _ => return Ok(None),
             },
-            _ => return forward(event),
+            _ => return Ok(None),
         };
 
-        Ok(Some(action))
+        Ok(self.execute(state, command))
+    }
+
+    /// Opens the overlay the command asks for, or returns the action to take.
+    fn execute(&mut self, state: &State, command: Command) -> Option<Action> {
+        let overlay = match command {
+            Command::Search => Overlay::Prompt(String::new()),
+            Command::FilterSpeakers => Overlay::SpeakerPrompt(state.speakers_state.query.clone()),
+            Command::Filter => Overlay::FilterPicker { cursor: 0 },
+            Command::Tracks => Overlay::TrackLegend,
+            Command::Help => Overlay::help(&self.keymap, state.mode),
+            command => return command.action(),
+        };
+
+        self.overlay = Some(overlay);
+        None
+    }
+}
+
+/// Draws what conference this is about and which version of the schedule is loaded.
+fn draw_header(state: &State, area: Rect, frame: &mut Frame<'_>) {
+    let conference = state.schedule.conference();
+
+    let mut left = vec![Span::raw(" "), Span::raw(conference.title.as_str()).bold()];
+    if !conference.acronym.is_empty() {
+        left.push(Span::raw(format!(" ({})", conference.acronym)));
     }
+
+    let right = match state.schedule.version() {
+        Some(version) => format!("version {version} "),
+        None => String::new(),
+    };
+
+    draw_bar(Line::from(left), Line::raw(right), area, frame);
+}
+
+/// Draws where the user currently is and what they're seeing, as well as the last notice.
+fn draw_status(state: &State, area: Rect, frame: &mut Frame<'_>) {
+    let start = state.selected_event().start;
+    let day = match state.schedule.day_of(start) {
+        Some(day) => format!(
+            "day {} · {}",
+            day.index,
+            day.date.format(DATE_FORMAT).unwrap()
+        ),
+        None => state.local(start).format(DATE_FORMAT).unwrap(),
+    };
+
+    let mut left = vec![
+        Span::raw(" "),
+        Span::raw(state.mode.name()).bold(),
+        Span::raw(" · "),
+        Span::raw(day),
+    ];
+    if !state.filter.is_empty() {
+        let filters = state
+            .filter
+            .iter()
+            .map(|(field, value)| format!("{field}={value}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        left.extend([Span::raw(" · filtered by "), Span::raw(filters)]);
+    }
+
+    let right = match &state.notice {
+        Some(notice) => format!("{notice} "),
+        None => String::new(),
+    };
+
+    draw_bar(Line::from(left), Line::raw(right), area, frame);
+}
+
+/// Draws a reversed line, with `right` aligned to the right edge and taking precedence over `left`.
+fn draw_bar(left: Line<'_>, right: Line<'_>, area: Rect, frame: &mut Frame<'_>) {
+    let style = Style::new().reversed();
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(right.width().try_into().unwrap_or(u16::MAX)),
+        ])
+        .split(area);
+
+    frame.render_widget(Paragraph::new(left).style(style), layout[0]);
+    frame.render_widget(Paragraph::new(right).style(style), layout[1]);
 }
 
 fn init_terminal<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
@@ -154,19 +321,136 @@ pub type TerminalEvent = crossterm::event::Event;
 
 pub const DATETIME_FORMAT_LONG: &'static [FormatItem<'static>] =
     format_description!("[year]-[month]-[day]  [hour] [minute]");
+pub const DATE_FORMAT: &[FormatItem<'static>] =
+    format_description!("[weekday repr:short] [year]-[month]-[day]");
 pub const DATETIME_FORMAT_SHORT: &'static [FormatItem<'static>] =
     format_description!("[hour] [minute]");
 
+/// Prefixed to the title of starred events.
+pub const FAVOURITE_MARKER: &str = "★ ";
+/// Drawn in the colour of a track in front of its name.
+pub const TRACK_SWATCH: &str = "■ ";
+
 pub fn helper_span(content: &str) -> Span<'_> {
-    Span::styled(content, Style::new().dark_gray())
+    Span::styled(content, Theme::current().helper())
+}
+
+/// Turns whatever is already drawn in `area` into an OSC 8 hyperlink to `url`, so terminals
+/// supporting those let the user open it right away.
+///
+/// ratatui doesn't know about escape sequences and would count them towards the width of a cell,
+/// so it never draws the cell after one. Hence the text is wrapped two cells at a time, with the
+/// second cell skipped on purpose since the first one already contains it. A last odd cell stays
+/// as it is, otherwise whatever follows the link wouldn't be drawn.
+///
+/// URLs containing control characters are left unlinked, since those could end the sequence early
+/// and sneak in arbitrary other ones. Anything else outside of printable ASCII is percent-encoded.
+pub fn hyperlink(buf: &mut Buffer, area: Rect, url: &str) {
+    if url.chars().any(char::is_control) {
+        return;
+    }
+    let url: String = url
+        .bytes()
+        .map(|byte| match byte {
+            b'!'..=b'~' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect();
+
+    for y in area.top()..area.bottom() {
+        for x in (area.left()..area.right().saturating_sub(1)).step_by(2) {
+            let text = format!("{}{}", buf.get(x, y).symbol(), buf.get(x + 1, y).symbol());
+            buf.get_mut(x + 1, y).set_skip(true);
+            buf.get_mut(x, y)
+                .set_symbol(&format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\"));
+        }
+    }
 }
 
 pub fn wrap(content: &str, width: usize) -> impl Iterator<Item = Line> {
     let mut opts = Options::new(width);
+    opts.word_splitter = word_splitter();
 
+    textwrap::wrap(content, opts).into_iter().map(Line::raw)
+}
+
+/// Splits words at the points where an English hyphenation dictionary allows to.
+fn word_splitter() -> WordSplitter {
     let dictionary =
         Standard::from_embedded(Language::EnglishUS).expect("embedded dict to be correct");
-    opts.word_splitter = WordSplitter::Hyphenation(dictionary);
+    WordSplitter::Hyphenation(dictionary)
+}
 
-    textwrap::wrap(content, opts).into_iter().map(Line::raw)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linked(text: &str, url: &str) -> Buffer {
+        let area = Rect::new(0, 0, text.len() as u16, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, text, Style::new());
+        hyperlink(&mut buf, area, url);
+        buf
+    }
+
+    #[test]
+    fn hyperlink_covers_pairs_of_cells() {
+        let buf = linked("link", "https://example.org");
+
+        assert_eq!(
+            buf.get(0, 0).symbol(),
+            "\x1b]8;;https://example.org\x1b\\li\x1b]8;;\x1b\\"
+        );
+        assert!(buf.get(1, 0).skip);
+        assert!(buf.get(2, 0).symbol().contains("nk"));
+    }
+
+    #[test]
+    fn hyperlink_leaves_odd_last_cell_alone() {
+        let buf = linked("abc", "https://example.org");
+
+        assert_eq!(buf.get(2, 0).symbol(), "c");
+        assert!(!buf.get(2, 0).skip);
+    }
+
+    #[test]
+    fn hyperlink_encodes_or_skips_unsafe_urls() {
+        let buf = linked("ab", "https://example.org/ä b");
+        assert!(buf
+            .get(0, 0)
+            .symbol()
+            .starts_with("\x1b]8;;https://example.org/%C3%A4%20b\x1b\\"));
+
+        for url in [
+            "https://example.org/\x1b]0;pwned\x07",
+            "https://example.org/\u{9b}",
+        ] {
+            let buf = linked("ab", url);
+            assert_eq!(buf.get(0, 0).symbol(), "a");
+            assert!(!buf.get(1, 0).skip);
+        }
+    }
+
+    fn bar(width: u16, left: &str, right: &str) -> String {
+        let mut terminal = Terminal::new(backend::TestBackend::new(width, 1)).unwrap();
+        terminal
+            .draw(|frame| draw_bar(Line::raw(left), Line::raw(right), frame.size(), frame))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..width).map(|x| buffer.get(x, 0).symbol()).collect()
+    }
+
+    #[test]
+    fn bars_put_the_right_side_first() {
+        assert_eq!(
+            bar(30, " Test Conference", "version 1.2 "),
+            " Test Conference  version 1.2 "
+        );
+        assert_eq!(
+            bar(16, " Test Conference", "version 1.2 "),
+            " Tesversion 1.2 "
+        );
+        assert_eq!(bar(8, " Test Conference", "version 1.2 "), "version ");
+    }
 }
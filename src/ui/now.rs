@@ -0,0 +1,137 @@
+//! What's running right now and what starts next, per room. Meant to be glanced at.
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    state::{
+        schedule::Event,
+        store::{RoomStatus, State},
+    },
+    DateTime,
+};
+
+use super::{color::Theme, helper_span, DATETIME_FORMAT_SHORT, FAVOURITE_MARKER};
+
+/// How many lines each room takes up.
+const ROOM_HEIGHT: u16 = 3;
+
+pub struct View<'state> {
+    pub state: &'state State,
+}
+
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let now = DateTime::now_utc();
+        let rooms = self.state.now_and_next(now);
+
+        let layout = Layout::default()
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(area);
+
+        let clock = self.state.local(now).format(DATETIME_FORMAT_SHORT).unwrap();
+        let header = Line::from(vec![helper_span("it is "), Span::raw(clock).bold()]);
+        frame.render_widget(Paragraph::new(header), layout[0]);
+
+        if rooms.is_empty() {
+            frame.render_widget(
+                Paragraph::new(helper_span("nothing running and nothing coming up"))
+                    .alignment(Alignment::Center),
+                layout[1],
+            );
+            return;
+        }
+
+        // rooms that don't fit are just cut off, scrolling would defeat the glancing
+        let areas = Layout::default()
+            .constraints(
+                (0..rooms.len())
+                    .map(|_| Constraint::Length(ROOM_HEIGHT))
+                    .chain([Constraint::Min(0)]),
+            )
+            .split(layout[1]);
+
+        for (status, area) in rooms.iter().zip(areas.iter()) {
+            self.room(status, now, *area, frame);
+        }
+    }
+}
+
+impl<'state> View<'state> {
+    /// Draws one room as a row of name, current event with its progress and next event.
+    fn room(&self, status: &RoomStatus, now: DateTime, area: Rect, frame: &mut Frame<'_>) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(18),
+                Constraint::Percentage(50),
+                // spacing so titles don't run into each other
+                Constraint::Length(2),
+                Constraint::Min(0),
+            ])
+            .split(area);
+        let rows = |area| {
+            Layout::default()
+                .constraints([Constraint::Length(1), Constraint::Length(1)])
+                .split(area)
+        };
+
+        frame.render_widget(Paragraph::new(Span::raw(status.room).bold()), columns[0]);
+
+        let current = rows(columns[1]);
+        match status.current {
+            Some(event) => {
+                frame.render_widget(Paragraph::new(self.title(event)), current[0]);
+
+                let elapsed = now - event.start;
+                let ratio = elapsed / event.duration;
+                let left = minutes(event.end() - now);
+                frame.render_widget(
+                    LineGauge::default()
+                        .ratio(ratio.clamp(0.0, 1.0))
+                        .label(format!("{left} left"))
+                        .gauge_style(Style::new().cyan()),
+                    current[1],
+                );
+            }
+            None => frame.render_widget(Paragraph::new(helper_span("nothing now")), current[0]),
+        }
+
+        let next = rows(columns[3]);
+        match status.next {
+            Some(event) => {
+                let start = self
+                    .state
+                    .local(event.start)
+                    .format(DATETIME_FORMAT_SHORT)
+                    .unwrap();
+                let until = minutes(event.start - now);
+                frame.render_widget(Paragraph::new(self.title(event)), next[0]);
+                frame.render_widget(
+                    Paragraph::new(Line::from(vec![
+                        Span::raw(start),
+                        helper_span(&format!("  in {until}")),
+                    ])),
+                    next[1],
+                );
+            }
+            None => frame.render_widget(Paragraph::new(helper_span("nothing next")), next[0]),
+        }
+    }
+
+    fn title<'event>(&self, event: &'event Event) -> Line<'event> {
+        let mut title = vec![Span::raw(event.title.as_str()).bold()];
+        if self.state.favourites.contains(&event.id) {
+            title.insert(
+                0,
+                Span::styled(FAVOURITE_MARKER, Theme::current().favourite()),
+            );
+        }
+        Line::from(title)
+    }
+}
+
+/// Formats the duration like `1h 20m`, leaving out seconds since nobody cares about them here.
+fn minutes(duration: time::Duration) -> String {
+    let duration = std::time::Duration::from_secs(duration.whole_minutes().unsigned_abs() * 60);
+    humantime::format_duration(duration).to_string()
+}
@@ -0,0 +1,541 @@
+//! Modal things drawn on top of the current [`super::View`], receiving all input while open.
+//!
+//! Unlike views, overlays live across frames, since they need to remember what the user typed or
+//! where their cursor is. That's pure UI state though, anything relevant to the application
+//! still only changes through [`Action`]s.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{
+        block::{Position, Title},
+        *,
+    },
+};
+
+use crate::{
+    state::{
+        filter::{Criterion, Field},
+        store::{Mode, State},
+    },
+    Action,
+};
+
+use super::{
+    color::{track_color, Theme},
+    helper_span,
+    keymap::{Command, KeyChord, Keymap, Scope},
+    TerminalEvent, TRACK_SWATCH,
+};
+
+pub enum Overlay {
+    /// Text typed into the search prompt so far.
+    Prompt(String),
+    /// Text typed into the speaker filter so far, which applies while typing.
+    SpeakerPrompt(String),
+    /// Choosing which values to filter by, with the cursor on the given entry of
+    /// [`filter_entries`].
+    FilterPicker { cursor: usize },
+    /// Which colour belongs to which track.
+    TrackLegend,
+    /// What each key does in the mode the overlay was opened in and in the overlays themselves, as
+    /// given by [`Keymap::bindings`].
+    Help {
+        bindings: Vec<(Command, Vec<KeyChord>)>,
+        /// Only the ones in [`OVERLAY_COMMANDS`].
+        overlay: Vec<(Command, Vec<KeyChord>)>,
+        scroll: u16,
+    },
+}
+
+/// What the overlays listing something react to, besides the key that opened them.
+const OVERLAY_COMMANDS: [Command; 7] = [
+    Command::Up,
+    Command::Down,
+    Command::ScrollUp,
+    Command::ScrollDown,
+    Command::Toggle,
+    Command::ClearFilter,
+    Command::Back,
+];
+
+impl Overlay {
+    /// Lists the keys of the given mode and of the overlays.
+    pub fn help(keymap: &Keymap, mode: Mode) -> Self {
+        let overlay = keymap
+            .bindings(Scope::Overlay)
+            .into_iter()
+            .filter(|(command, _)| OVERLAY_COMMANDS.contains(command))
+            .collect();
+
+        Self::Help {
+            bindings: keymap.bindings(mode),
+            overlay,
+            scroll: 0,
+        }
+    }
+
+    pub fn draw(&self, state: &State, keymap: &Keymap, frame: &mut Frame<'_>) {
+        match self {
+            Self::Prompt(prompt) | Self::SpeakerPrompt(prompt) => draw_prompt(prompt, frame),
+            Self::FilterPicker { cursor } => draw_filter_picker(*cursor, state, keymap, frame),
+            Self::TrackLegend => draw_track_legend(state, keymap, frame),
+            Self::Help {
+                bindings,
+                overlay,
+                scroll,
+            } => draw_help(bindings, overlay, *scroll, state, keymap, frame),
+        }
+    }
+
+    /// Handles the event, returning what the overlay looks like afterwards ([`None`] if it was
+    /// closed) and what action to take. Keys do what the keymap binds them to in
+    /// [`Scope::Overlay`], except in the prompts, which take them as text.
+    pub fn process(
+        self,
+        state: &State,
+        keymap: &Keymap,
+        event: TerminalEvent,
+    ) -> (Option<Self>, Option<Action>) {
+        let TerminalEvent::Key(
+            key @ KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            },
+        ) = event
+        else {
+            return (Some(self), None);
+        };
+
+        // chords with ctrl or alt aren't meant to be typed
+        let code = match key.code {
+            KeyCode::Char(_) if !KeyModifiers::SHIFT.contains(key.modifiers) => KeyCode::Null,
+            code => code,
+        };
+
+        let command = keymap.get(Scope::Overlay, key.into());
+        // the key that opened the overlay closes it again, just like the one for quitting does
+        let closes = |opened_by| {
+            matches!(command, Some(Command::Back | Command::Quit)) || command == Some(opened_by)
+        };
+
+        match self {
+            Self::Prompt(mut prompt) => match code {
+                KeyCode::Char(ch) => {
+                    prompt.push(ch);
+                    (Some(Self::Prompt(prompt)), None)
+                }
+                KeyCode::Backspace if prompt.is_empty() => (None, None),
+                KeyCode::Backspace => {
+                    prompt.pop();
+                    (Some(Self::Prompt(prompt)), None)
+                }
+                KeyCode::Enter if prompt.is_empty() => (None, None),
+                KeyCode::Enter => (None, Some(Action::Search(prompt))),
+                KeyCode::Esc => (None, None),
+                _ => (Some(Self::Prompt(prompt)), None),
+            },
+            Self::SpeakerPrompt(mut prompt) => {
+                match code {
+                    KeyCode::Char(ch) => prompt.push(ch),
+                    KeyCode::Backspace if prompt.is_empty() => return (None, None),
+                    KeyCode::Backspace => {
+                        prompt.pop();
+                    }
+                    // the filter is applied already, so there's nothing left to do
+                    KeyCode::Enter | KeyCode::Esc => return (None, None),
+                    _ => return (Some(Self::SpeakerPrompt(prompt)), None),
+                }
+
+                let action = Action::FilterSpeakers(prompt.clone());
+                (Some(Self::SpeakerPrompt(prompt)), Some(action))
+            }
+            Self::FilterPicker { cursor } => {
+                let entries = filter_entries(state);
+                let last = entries.len().saturating_sub(1);
+
+                let (cursor, action) = match command {
+                    Some(Command::Down | Command::ScrollDown) => {
+                        (cursor.saturating_add(1).min(last), None)
+                    }
+                    Some(Command::Up | Command::ScrollUp) => (cursor.saturating_sub(1), None),
+                    Some(Command::Toggle) => {
+                        let action = entries.into_iter().nth(cursor).map(Action::ToggleFilter);
+                        (cursor, action)
+                    }
+                    Some(Command::ClearFilter) => (cursor, Some(Action::ClearFilter)),
+                    _ if closes(Command::Filter) => return (None, None),
+                    _ => (cursor, None),
+                };
+
+                (Some(Self::FilterPicker { cursor }), action)
+            }
+            Self::TrackLegend => match closes(Command::Tracks) {
+                true => (None, None),
+                false => (Some(Self::TrackLegend), None),
+            },
+            Self::Help {
+                bindings,
+                overlay,
+                scroll,
+            } => {
+                let scroll = match command {
+                    Some(Command::Down | Command::ScrollDown) => {
+                        let last = help_lines(&bindings, &overlay).len().saturating_sub(1);
+                        scroll
+                            .saturating_add(1)
+                            .min(last.try_into().unwrap_or(u16::MAX))
+                    }
+                    Some(Command::Up | Command::ScrollUp) => scroll.saturating_sub(1),
+                    _ if closes(Command::Help) => return (None, None),
+                    _ => scroll,
+                };
+                let help = Self::Help {
+                    bindings,
+                    overlay,
+                    scroll,
+                };
+                (Some(help), None)
+            }
+        }
+    }
+}
+
+/// Draws the search prompt over the last line of the frame, like vim does.
+fn draw_prompt(prompt: &str, frame: &mut Frame<'_>) {
+    let area = frame.size();
+    let line_area = Rect {
+        y: area.bottom().saturating_sub(1),
+        height: 1.min(area.height),
+        ..area
+    };
+
+    let line = Line::from(vec![
+        Span::raw("/"),
+        Span::raw(prompt),
+        Span::raw(" ").reversed(),
+    ]);
+    frame.render_widget(Clear, line_area);
+    frame.render_widget(Paragraph::new(line), line_area);
+}
+
+/// All values that could be filtered by, grouped by field.
+fn filter_entries(state: &State) -> Vec<Criterion> {
+    Field::ALL
+        .into_iter()
+        .flat_map(|field| {
+            field
+                .values(&state.schedule)
+                .into_iter()
+                .map(move |value| Criterion {
+                    field,
+                    value: value.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn draw_filter_picker(cursor: usize, state: &State, keymap: &Keymap, frame: &mut Frame<'_>) {
+    let area = centered(frame.size(), 60, 80);
+
+    // each field gets its own heading, which can't be selected
+    let mut items = Vec::new();
+    let mut selected = None;
+    let mut last_field = None;
+    for (idx, criterion) in filter_entries(state).into_iter().enumerate() {
+        if last_field != Some(criterion.field) {
+            if last_field.is_some() {
+                items.push(ListItem::new(""));
+            }
+            items.push(ListItem::new(Line::from(
+                Span::raw(criterion.field.name()).bold(),
+            )));
+            last_field = Some(criterion.field);
+        }
+
+        if idx == cursor {
+            selected = Some(items.len());
+        }
+
+        let mark = match state.filter.contains(criterion.field, &criterion.value) {
+            true => "[x] ",
+            false => "[ ] ",
+        };
+        items.push(ListItem::new(Line::from(vec![
+            helper_span(mark),
+            Span::raw(criterion.value),
+        ])));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" filter ")
+        .title(hint(
+            keymap,
+            &[
+                (&[Command::Toggle], "toggle"),
+                (&[Command::ClearFilter], "clear"),
+                (&[Command::Back], "close"),
+            ],
+        ));
+    let mut list_state = ListState::default().with_selected(selected);
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(block)
+            .highlight_style(Style::new().reversed()),
+        area,
+        &mut list_state,
+    );
+}
+
+fn draw_track_legend(state: &State, keymap: &Keymap, frame: &mut Frame<'_>) {
+    let tracks = &state.schedule.conference().tracks;
+
+    let lines = match tracks.is_empty() {
+        true => vec![Line::from(helper_span("the schedule declares no tracks"))],
+        false => tracks
+            .iter()
+            .map(|track| {
+                let swatch = match track_color(&state.schedule, &track.name) {
+                    Some(color) => Span::raw(TRACK_SWATCH).fg(color),
+                    None => helper_span(TRACK_SWATCH),
+                };
+                Line::from(vec![swatch, Span::raw(track.name.as_str())])
+            })
+            .collect(),
+    };
+
+    let area = centered(frame.size(), 50, 60);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" tracks ")
+        .title(hint(keymap, &[(&[Command::Back], "close")]));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The lines of the help, first the keys of the mode, then the ones of the overlays.
+fn help_lines(
+    bindings: &[(Command, Vec<KeyChord>)],
+    overlay: &[(Command, Vec<KeyChord>)],
+) -> Vec<Line<'static>> {
+    let keys = |bindings: &[(Command, Vec<KeyChord>)]| -> Vec<String> {
+        bindings
+            .iter()
+            .map(|(_, chords)| {
+                chords
+                    .iter()
+                    .map(KeyChord::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect()
+    };
+    let (keys, overlay_keys) = (keys(bindings), keys(overlay));
+    let width = keys
+        .iter()
+        .chain(&overlay_keys)
+        .map(|keys| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let line = |((command, _), keys): (&(Command, _), String)| {
+        Line::from(vec![
+            Span::raw(format!(" {keys:<width$}  ")).bold(),
+            Span::raw(command.description()),
+        ])
+    };
+    let mut lines: Vec<_> = bindings.iter().zip(keys).map(line).collect();
+    if !overlay.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from(helper_span(
+            " in the filter picker, track legend and this help",
+        )));
+        lines.extend(overlay.iter().zip(overlay_keys).map(line));
+    }
+    lines
+}
+
+fn draw_help(
+    bindings: &[(Command, Vec<KeyChord>)],
+    overlay: &[(Command, Vec<KeyChord>)],
+    scroll: u16,
+    state: &State,
+    keymap: &Keymap,
+    frame: &mut Frame<'_>,
+) {
+    let lines = help_lines(bindings, overlay);
+
+    let area = centered(frame.size(), 70, 80);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" keys in {} mode ", state.mode.name()))
+        .title(hint(
+            keymap,
+            &[
+                (&[Command::Down, Command::Up], "scroll"),
+                (&[Command::Back], "close"),
+            ],
+        ));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block).scroll((scroll, 0)), area);
+}
+
+/// The line at the bottom edge of an overlay telling which keys do what, see [`Keymap::hint`].
+fn hint(keymap: &Keymap, entries: &[(&[Command], &str)]) -> Title<'static> {
+    let hint = keymap.hint(Scope::Overlay, entries);
+    Title::from(Span::styled(format!(" {hint} "), Theme::current().helper()))
+        .position(Position::Bottom)
+}
+
+/// Returns a rect in the center of `area`, taking up the given percentages of it.
+pub fn centered(area: Rect, width_percent: u16, height_percent: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - height_percent) / 2),
+            Constraint::Percentage(height_percent),
+            Constraint::Percentage((100 - height_percent) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - width_percent) / 2),
+            Constraint::Percentage(width_percent),
+            Constraint::Percentage((100 - width_percent) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::store::fixture::state,
+        ui::keymap::{Binding, Preset},
+    };
+
+    /// Feeds the keys to the overlay one after another, returning what's left of it and the action
+    /// of the last key.
+    fn press(
+        overlay: Overlay,
+        state: &State,
+        keymap: &Keymap,
+        keys: &[KeyChord],
+    ) -> (Option<Overlay>, Option<Action>) {
+        let mut overlay = Some(overlay);
+        let mut action = None;
+        for key in keys {
+            let event = TerminalEvent::Key(KeyEvent::new(key.code, key.modifiers));
+            let Some(open) = overlay else {
+                panic!("overlay closed before {key}");
+            };
+            (overlay, action) = open.process(state, keymap, event);
+        }
+        (overlay, action)
+    }
+
+    #[test]
+    fn filter_picker_follows_the_keymap() {
+        let state = state("overlay-keymap", &[]);
+        let keymap = Keymap::new(Preset::Emacs, &[]);
+        let picker = Overlay::FilterPicker { cursor: 0 };
+        let third = filter_entries(&state)[2].clone();
+
+        let keys = [
+            KeyChord::ctrl('n'),
+            KeyChord::ctrl('n'),
+            KeyChord::ctrl('n'),
+        ];
+        let (picker, _) = press(picker, &state, &keymap, &keys);
+        let keys = [KeyChord::ctrl('p'), KeyChord::new(KeyCode::Enter)];
+        let (picker, action) = press(picker.unwrap(), &state, &keymap, &keys);
+        assert!(matches!(picker, Some(Overlay::FilterPicker { cursor: 2 })));
+        assert!(matches!(action, Some(Action::ToggleFilter(criterion)) if criterion == third));
+
+        let (picker, _) = press(picker.unwrap(), &state, &keymap, &[KeyChord::ctrl('g')]);
+        assert!(picker.is_none());
+    }
+
+    #[test]
+    fn overlays_close_with_the_key_that_opened_them() {
+        let state = state("overlay-close", &[]);
+        let keymap = Keymap::new(Preset::Vim, &[]);
+
+        let (legend, _) = press(
+            Overlay::TrackLegend,
+            &state,
+            &keymap,
+            &[KeyChord::char('F')],
+        );
+        let (legend, _) = press(legend.unwrap(), &state, &keymap, &[KeyChord::char('T')]);
+        assert!(legend.is_none());
+    }
+
+    #[test]
+    fn user_bindings_apply_in_overlays() {
+        let state = state("overlay-user", &[]);
+        let keymap = Keymap::new(
+            Preset::Arrows,
+            &[
+                Binding {
+                    scope: Some(Scope::Overlay),
+                    chord: KeyChord::char('x'),
+                    command: Command::ClearFilter,
+                },
+                Binding {
+                    scope: None,
+                    chord: KeyChord::char('Q'),
+                    command: Command::Quit,
+                },
+            ],
+        );
+
+        let picker = Overlay::FilterPicker { cursor: 0 };
+        let (picker, action) = press(picker, &state, &keymap, &[KeyChord::char('x')]);
+        assert!(matches!(action, Some(Action::ClearFilter)));
+        let (picker, _) = press(picker.unwrap(), &state, &keymap, &[KeyChord::char('Q')]);
+        assert!(picker.is_none());
+    }
+
+    #[test]
+    fn prompts_take_keys_as_text() {
+        let state = state("overlay-prompt", &[]);
+        let keymap = Keymap::new(Preset::Emacs, &[]);
+
+        let keys = [
+            KeyChord::char('c'),
+            KeyChord::ctrl('n'),
+            KeyChord::char('q'),
+            KeyChord::new(KeyCode::Enter),
+        ];
+        let (prompt, action) = press(Overlay::Prompt(String::new()), &state, &keymap, &keys);
+        assert!(prompt.is_none());
+        assert!(matches!(action, Some(Action::Search(query)) if query == "cq"));
+    }
+
+    #[test]
+    fn help_lists_the_overlay_keys() {
+        let keymap = Keymap::new(Preset::Vim, &[]);
+        let Overlay::Help { overlay, .. } = Overlay::help(&keymap, Mode::Grid) else {
+            unreachable!("help is help");
+        };
+
+        let toggle = overlay
+            .iter()
+            .find(|(command, _)| *command == Command::Toggle)
+            .unwrap();
+        assert_eq!(
+            toggle.1,
+            [KeyChord::char(' '), KeyChord::new(KeyCode::Enter)]
+        );
+        // quitting closes them, but that's not what it's listed for
+        assert!(overlay.iter().all(|(command, _)| *command != Command::Quit));
+    }
+}
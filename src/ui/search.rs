@@ -0,0 +1,55 @@
+//! All events matching the last search, in chronological order.
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::state::store::State;
+
+use super::{helper_span, DATETIME_FORMAT_LONG};
+
+pub struct View<'state> {
+    pub state: &'state State,
+}
+
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let search = &self.state.search_state;
+
+        let layout = Layout::default()
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(area);
+
+        let count = format!("  ({})", search.matches.len());
+        let summary = Line::from(vec![
+            helper_span("matches for "),
+            Span::raw(search.query.as_str()).bold(),
+            helper_span(&count),
+        ]);
+        frame.render_widget(Paragraph::new(summary), layout[0]);
+
+        let selected = self.state.selected_event();
+        let selected_idx = search.matches.iter().position(|id| *id == selected.id);
+
+        let items = search.matches.iter().map(|id| {
+            let event = &self.state.schedule[id];
+            let start = self
+                .state
+                .local(event.start)
+                .format(DATETIME_FORMAT_LONG)
+                .unwrap();
+
+            ListItem::new(Line::from(vec![
+                Span::raw(start),
+                Span::raw("  "),
+                Span::raw(format!("{:<16}", event.room)),
+                Span::raw(event.title.as_str()).bold(),
+            ]))
+        });
+
+        let mut list_state = ListState::default().with_selected(selected_idx);
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::new().reversed()),
+            layout[1],
+            &mut list_state,
+        );
+    }
+}
@@ -1,58 +1,79 @@
 //! One specific event with all its gory details, presented like the first page of a paper.
 
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use itertools::intersperse;
 use ratatui::{prelude::*, widgets::*};
-use time::UtcOffset;
 
 use crate::{
     state::{
-        schedule,
-        store::{Mode, State},
+        schedule::{self, markup::Document, PersonId},
+        store::State,
     },
-    Action, DateTime, VerticalDirection,
+    Action,
 };
 
-use super::{helper_span, TerminalEvent, DATETIME_FORMAT_LONG, DATETIME_FORMAT_SHORT};
+use super::{
+    color::{track_color, Theme},
+    helper_span, markup, DATETIME_FORMAT_LONG, DATETIME_FORMAT_SHORT, FAVOURITE_MARKER,
+    TRACK_SWATCH,
+};
 
 pub struct View<'state> {
     pub state: &'state State,
+    /// Where each link was drawn together with its number, so clicks can open them.
+    links: Vec<(Rect, usize)>,
+    /// Where each speaker's name was drawn, so clicks can list their events.
+    persons: Vec<(Rect, PersonId)>,
+}
+
+impl<'state> View<'state> {
+    pub fn new(state: &'state State) -> Self {
+        Self {
+            state,
+            links: Vec::new(),
+            persons: Vec::new(),
+        }
+    }
 }
 
 impl<'state> super::View for View<'state> {
-    fn draw(&mut self, frame: &mut Frame<'_>) {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
         let layout = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Ratio(1, 4), Constraint::Min(0)])
-            .split(frame.size());
+            .split(area);
 
+        // parsed only once, both the links and the text need them
         let event = self.state.selected_event();
+        let documents = event.documents();
         let mut render = RenderState {
             view: self,
             event,
             frame,
+            urls: event.urls_in(&documents),
+            documents,
+            links: Vec::new(),
+            persons: Vec::new(),
         };
 
         render.metadata(layout[0]);
         render.content(layout[1]);
+
+        let RenderState { links, persons, .. } = render;
+        self.links = links;
+        self.persons = persons;
     }
 
-    fn process(&mut self, event: super::TerminalEvent) -> Option<crate::Action> {
-        let action = match event {
-            TerminalEvent::Key(KeyEvent {
-                code,
-                kind: KeyEventKind::Press,
-                ..
-            }) => match code {
-                KeyCode::Esc | KeyCode::Enter => Action::SwitchTo(Mode::Grid),
-                KeyCode::Char('k') => Action::Scroll(VerticalDirection::Up),
-                KeyCode::Char('j') => Action::Scroll(VerticalDirection::Down),
-                _ => return None,
-            },
-            _ => return None,
+    fn click(&mut self, column: u16, row: u16, _double: bool) -> Option<Action> {
+        let hit = |area: &Rect| {
+            (area.left()..area.right()).contains(&column)
+                && (area.top()..area.bottom()).contains(&row)
         };
 
-        Some(action)
+        if let Some((_, id)) = self.persons.iter().find(|(area, _)| hit(area)) {
+            return Some(Action::ShowSpeaker(*id));
+        }
+        let (_, n) = self.links.iter().find(|(area, _)| hit(area))?;
+        Some(Action::OpenLink(*n))
     }
 }
 
@@ -60,6 +81,14 @@ struct RenderState<'view, 'state, 'frame, 'life> {
     view: &'view View<'state>,
     event: &'state schedule::Event,
     frame: &'frame mut Frame<'life>,
+    /// Numbered from 1 on, see [`schedule::Event::urls`].
+    urls: Vec<(String, String)>,
+    /// The abstract and description, see [`schedule::Event::documents`].
+    documents: [Document; 2],
+    /// Where each link was drawn, see [`View::links`].
+    links: Vec<(Rect, usize)>,
+    /// Where each name was drawn, see [`View::persons`].
+    persons: Vec<(Rect, PersonId)>,
 }
 
 impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
@@ -67,17 +96,16 @@ impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
         // the short format with only the time is ideal when the event is today
         // the long format should be displayed otherwise
         // that check is done for start/end individually
-        let now = DateTime::now_utc();
+        let state = self.view.state;
 
         let [start, end]: [Span; 2] = [self.event.start, self.event.end()]
             .into_iter()
             .map(|point| {
-                let is_today = now.date() == point.to_offset(UtcOffset::UTC).date();
-                let format = match is_today {
+                let format = match state.is_today(point) {
                     true => DATETIME_FORMAT_SHORT,
                     false => DATETIME_FORMAT_LONG,
                 };
-                let point = point.format(format).unwrap();
+                let point = state.local(point).format(format).unwrap();
                 Span::raw(point)
             })
             .collect::<Vec<_>>()
@@ -87,6 +115,11 @@ impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
         let duration = humantime::Duration::from(self.event.duration.unsigned_abs());
         let duration = Span::raw(duration.to_string());
 
+        let mut track = Line::raw(self.event.track.as_str());
+        if let Some(color) = track_color(&state.schedule, &self.event.track) {
+            track.spans.insert(0, Span::raw(TRACK_SWATCH).fg(color));
+        }
+
         let vert_layout = Layout::default()
             .constraints([Constraint::Length(4), Constraint::Min(0)])
             .split(container);
@@ -96,34 +129,75 @@ impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
             .constraints([Constraint::Length(7), Constraint::Min(0)])
             .split(vert_layout[1]);
 
-        self.frame.render_widget(
-            Paragraph::new(
-                ["where", "when", "+", "=", "", "track", "type"]
-                    .into_iter()
-                    .map(|label| vec![helper_span(label), Span::raw(" ")])
-                    .map(Line::from)
-                    .collect::<Vec<_>>(),
-            )
-            .alignment(Alignment::Right),
-            layout[0],
+        let mut labels = vec!["where", "when", "+", "=", "", "track", "type"];
+        let mut values = vec![
+            Line::from(self.event.room.as_str()),
+            Line::from(start),
+            Line::from(duration),
+            Line::from(end),
+            Line::default(),
+            track,
+            Line::from(self.event.r#type.as_str()),
+        ];
+
+        // some only attend in person if there's no other way to watch it
+        let recording = &self.event.recording;
+        let recorded = match (recording.recorded, &recording.license) {
+            (Some(false), _) => Some(Line::from(Span::raw("will not be recorded").bold())),
+            (Some(true), Some(license)) => Some(Line::from(vec![
+                Span::raw("recorded"),
+                helper_span(", "),
+                Span::raw(license.as_str()),
+            ])),
+            (Some(true), None) => Some(Line::from("recorded")),
+            (None, _) => None,
+        };
+        if let Some(recorded) = recorded {
+            labels.push("record");
+            values.push(recorded);
+        }
+        labels.push("");
+        values.push(Line::default());
+
+        // links are numbered, so they can be opened by typing that number
+        let mut labels: Vec<_> = labels
+            .into_iter()
+            .map(|label| Line::from(vec![helper_span(label), Span::raw(" ")]))
+            .collect();
+        let first_link_line = labels.len();
+        labels.extend(
+            (1..=self.urls.len())
+                .map(|n| Line::from(vec![Span::raw(n.to_string()).bold(), Span::raw(" ")])),
+        );
+
+        values.extend(
+            self.urls
+                .iter()
+                .map(|(title, _)| Line::from(Span::raw(title.as_str()).underlined())),
         );
+
         self.frame.render_widget(
-            Paragraph::new(
-                [
-                    Span::raw(self.event.room.as_str()),
-                    start,
-                    duration,
-                    end,
-                    Span::raw(""),
-                    Span::raw(self.event.track.as_str()),
-                    Span::raw(self.event.r#type.as_str()),
-                ]
-                .into_iter()
-                .map(Line::from)
-                .collect::<Vec<_>>(),
-            ),
-            layout[1],
+            Paragraph::new(labels).alignment(Alignment::Right),
+            layout[0],
         );
+        self.frame.render_widget(Paragraph::new(values), layout[1]);
+
+        for (idx, (title, url)) in self.urls.iter().enumerate() {
+            let area = Rect {
+                y: layout[1].y + (first_link_line + idx) as u16,
+                height: 1,
+                width: title.chars().count().try_into().unwrap_or(u16::MAX),
+                ..layout[1]
+            }
+            .intersection(layout[1]);
+            if area.area() == 0 {
+                // didn't fit anymore
+                continue;
+            }
+
+            super::hyperlink(self.frame.buffer_mut(), area, url);
+            self.links.push((area, idx + 1));
+        }
     }
 
     fn content(&mut self, container: Rect) {
@@ -136,7 +210,13 @@ impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
     }
 
     fn header(&mut self, container: Rect) {
-        let title = Span::raw(&self.event.title).bold();
+        let mut title = vec![Span::raw(&self.event.title).bold()];
+        if self.view.state.favourites.contains(&self.event.id) {
+            title.insert(
+                0,
+                Span::styled(FAVOURITE_MARKER, Theme::current().favourite()),
+            );
+        }
         let subtitle = Span::raw(&self.event.subtitle).italic();
 
         // the individual persons should be concatenated with commas in-between
@@ -157,31 +237,203 @@ impl<'view, 'state, 'frame, 'life> RenderState<'view, 'state, 'frame, 'life> {
             .collect::<Vec<_>>();
         persons.insert(0, helper_span("by "));
 
-        let lines = vec![
-            Line::from(title),
-            Line::from(subtitle),
-            Line::raw(""),
-            Line::from(persons),
-        ];
+        let lines = vec![Line::from(title), Line::from(subtitle)];
         self.frame.render_widget(
             Paragraph::new(lines).alignment(Alignment::Center),
             container,
         );
+
+        // centered by hand, so it's known where each name ends up for clicking on it
+        let persons = Line::from(persons);
+        let width = u16::try_from(persons.width())
+            .unwrap_or(u16::MAX)
+            .min(container.width);
+        let area = Rect {
+            x: container.x + (container.width - width) / 2,
+            y: container.y.saturating_add(3),
+            width,
+            height: 1,
+        }
+        .intersection(container);
+
+        // names and separators alternate, after the "by"
+        let mut x = area.x;
+        for (idx, span) in persons.spans.iter().enumerate() {
+            let span_width = u16::try_from(span.width()).unwrap_or(u16::MAX);
+            if idx % 2 == 1 {
+                let name = Rect {
+                    x,
+                    width: span_width,
+                    ..area
+                }
+                .intersection(area);
+                if name.area() > 0 {
+                    self.persons.push((name, self.event.persons[idx / 2]));
+                }
+            }
+            x = x.saturating_add(span_width);
+        }
+
+        self.frame.render_widget(Paragraph::new(persons), area);
     }
 
     fn text(&mut self, container: Rect) {
         // ratatui seems to perform no wrapping on its own
-        // so let's use the textwrap crate instead
-
-        let wrap = |content| super::wrap(content, container.width as usize);
+        // so let's use the textwrap crate instead, which the markup rendering does
+        let number = |url: &str| {
+            self.urls
+                .iter()
+                .position(|(_, known)| known == url)
+                .map(|idx| idx + 1)
+        };
 
-        let mut text = Text::from(helper_span("abstract"));
-        text.extend(wrap(&self.event.r#abstract));
-        text.extend([Span::raw(""), helper_span("description")]);
-        text.extend(wrap(&self.event.description));
+        let mut lines = Vec::new();
+        let mut links = Vec::new();
+        for (label, document) in ["abstract", "description"].into_iter().zip(&self.documents) {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(helper_span(label)));
 
-        let paragraph = Paragraph::new(text).scroll((self.view.state.single_state.scroll_at, 0));
+            let rendered = markup::render(document, container.width as usize, number);
+            links.extend(
+                rendered
+                    .links
+                    .into_iter()
+                    .map(|(line, columns, url)| (lines.len() + line, columns, url)),
+            );
+            lines.extend(rendered.lines);
+        }
 
+        let scroll = self.view.state.single_state.scroll_at;
+        let paragraph = Paragraph::new(lines).scroll((scroll, 0));
         self.frame.render_widget(paragraph, container);
+
+        // only the links scrolled into view can be clicked
+        for (line, columns, url) in links {
+            let Some(row) = line.checked_sub(scroll.into()) else {
+                continue;
+            };
+            let area = Rect {
+                x: container
+                    .x
+                    .saturating_add(columns.start.try_into().unwrap_or(u16::MAX)),
+                y: container
+                    .y
+                    .saturating_add(row.try_into().unwrap_or(u16::MAX)),
+                width: columns.len().try_into().unwrap_or(u16::MAX),
+                height: 1,
+            }
+            .intersection(container);
+            if area.area() == 0 {
+                continue;
+            }
+
+            super::hyperlink(self.frame.buffer_mut(), area, &url);
+            if let Some(n) = number(&url) {
+                self.links.push((area, n));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::{
+        state::{schedule::fixture::event, store::fixture::state, Update},
+        ui::View as _,
+    };
+
+    /// Draws the view onto a terminal of the given size, returning what ended up there.
+    fn draw(view: &mut View<'_>, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| view.draw(frame, frame.size()))
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// The text shown in the area, without the escape sequences around hyperlinks.
+    fn text(buffer: &Buffer, area: Rect) -> String {
+        let mut text = String::new();
+        for y in area.top()..area.bottom() {
+            let mut x = area.left();
+            while x < area.right() {
+                let symbol = buffer.get(x, y).symbol();
+                x += 1;
+                let Some(linked) = symbol.strip_prefix("\x1b]8;;") else {
+                    text.push_str(symbol);
+                    continue;
+                };
+
+                // each linked symbol covers the cell after it, too
+                let (_, linked) = linked.split_once("\x1b\\").unwrap();
+                let (linked, _) = linked.split_once("\x1b]8").unwrap();
+                text.push_str(linked);
+                x += 1;
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn clicks_on_names_show_the_speaker() {
+        let mut state = state("single-names", &[]);
+        state.update(Action::SelectEvent(event(2)));
+        let mut view = View::new(&state);
+        let buffer = draw(&mut view, 120, 40);
+
+        let names: Vec<_> = view
+            .persons
+            .iter()
+            .map(|(area, _)| text(&buffer, *area))
+            .collect();
+        assert_eq!(names, ["John Smith", "Jane Doe"]);
+
+        for (area, id) in view.persons.clone() {
+            let action = view.click(area.right() - 1, area.y, false);
+            assert!(matches!(action, Some(Action::ShowSpeaker(clicked)) if clicked == id));
+        }
+        // the "and" between them
+        let (first, _) = view.persons[0];
+        assert!(view.click(first.right() + 1, first.y, false).is_none());
+    }
+
+    #[test]
+    fn clicks_on_links_open_them() {
+        let mut state = state("single-links", &[]);
+        state.update(Action::SelectEvent(event(1)));
+        let mut view = View::new(&state);
+        let buffer = draw(&mut view, 120, 40);
+
+        let urls = state.selected_event().urls();
+        let clicked: Vec<_> = view
+            .links
+            .clone()
+            .into_iter()
+            .map(|(area, n)| {
+                let action = view.click(area.x, area.y, false);
+                assert!(matches!(action, Some(Action::OpenLink(opened)) if opened == n));
+                (text(&buffer, area), urls[n - 1].1.as_str())
+            })
+            .collect();
+
+        // the metadata lists all of them, the abstract links to the wiki inline
+        assert_eq!(
+            clicked,
+            [
+                ("event page".to_string(), "https://example.org/tc23/talk/1/"),
+                ("Map".to_string(), "https://example.org/tc23/map"),
+                ("the wiki".to_string(), "https://example.org/wiki"),
+                (
+                    "feedback".to_string(),
+                    "https://example.org/tc23/talk/1/feedback"
+                ),
+                ("the wiki".to_string(), "https://example.org/wiki"),
+            ]
+        );
     }
 }
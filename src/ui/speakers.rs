@@ -0,0 +1,149 @@
+//! Everyone speaking at the conference sorted by name, next to the events the selected one
+//! appears in.
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    state::{
+        schedule::{EventId, PersonId},
+        store::{Mode, State},
+    },
+    Action,
+};
+
+use super::{helper_span, DATETIME_FORMAT_LONG};
+
+pub struct View<'state> {
+    pub state: &'state State,
+    /// Where each visible speaker was drawn, so clicks can select them.
+    persons: Vec<(Rect, PersonId)>,
+    /// Same for the events of the selected speaker.
+    events: Vec<(Rect, EventId)>,
+}
+
+impl<'state> View<'state> {
+    pub fn new(state: &'state State) -> Self {
+        Self {
+            state,
+            persons: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl<'state> super::View for View<'state> {
+    fn draw(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let speakers = &self.state.speakers_state;
+
+        let layout = Layout::default()
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(area);
+
+        let count = format!("  ({})", speakers.matches.len());
+        let summary = match speakers.query.is_empty() {
+            true => Line::from(vec![helper_span("all speakers"), helper_span(&count)]),
+            false => Line::from(vec![
+                helper_span("speakers matching "),
+                Span::raw(speakers.query.as_str()).bold(),
+                helper_span(&count),
+            ]),
+        };
+        frame.render_widget(Paragraph::new(summary), layout[0]);
+
+        if speakers.matches.is_empty() {
+            frame.render_widget(
+                Paragraph::new(helper_span("nobody's name matches")).alignment(Alignment::Center),
+                layout[1],
+            );
+            return;
+        }
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3), Constraint::Min(0)])
+            .split(layout[1]);
+
+        let names = speakers
+            .matches
+            .iter()
+            .map(|id| ListItem::new(self.state.schedule[id].name.as_str()));
+        let selected_idx = speakers
+            .selected
+            .and_then(|selected| speakers.matches.iter().position(|id| *id == selected));
+        self.persons = list(frame, columns[0], names, selected_idx, &speakers.matches);
+
+        let Some(person) = speakers.selected else {
+            return;
+        };
+        let events = self.state.schedule.events_of(&person);
+        let selected = self.state.selected_event();
+        let selected_idx = events.iter().position(|id| *id == selected.id);
+
+        let items = events.iter().map(|id| {
+            let event = &self.state.schedule[id];
+            let start = self
+                .state
+                .local(event.start)
+                .format(DATETIME_FORMAT_LONG)
+                .unwrap();
+
+            ListItem::new(Line::from(vec![
+                Span::raw(start),
+                Span::raw("  "),
+                Span::raw(format!("{:<16}", event.room)),
+                Span::raw(event.title.as_str()).bold(),
+            ]))
+        });
+        self.events = list(frame, columns[1], items, selected_idx, events);
+    }
+
+    fn click(&mut self, column: u16, row: u16, double: bool) -> Option<Action> {
+        let hit = |area: &Rect| {
+            (area.left()..area.right()).contains(&column)
+                && (area.top()..area.bottom()).contains(&row)
+        };
+
+        if let Some((_, id)) = self.persons.iter().find(|(area, _)| hit(area)) {
+            return Some(Action::ShowSpeaker(*id));
+        }
+
+        let (_, id) = self.events.iter().find(|(area, _)| hit(area))?;
+        // the first click of the two selected it already
+        Some(match double {
+            true => Action::SwitchTo(Mode::Single),
+            false => Action::SelectEvent(*id),
+        })
+    }
+}
+
+/// Draws the items as a list, returning where each visible one of `ids` ended up.
+fn list<'a, T: Copy>(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    items: impl Iterator<Item = ListItem<'a>>,
+    selected: Option<usize>,
+    ids: &[T],
+) -> Vec<(Rect, T)> {
+    let mut list_state = ListState::default().with_selected(selected);
+    frame.render_stateful_widget(
+        List::new(items.collect::<Vec<_>>()).highlight_style(Style::new().reversed()),
+        area,
+        &mut list_state,
+    );
+
+    // each item is one line high, so the rows follow from where the list scrolled to
+    ids.iter()
+        .skip(list_state.offset())
+        .zip(area.top()..area.bottom())
+        .map(|(id, y)| {
+            (
+                Rect {
+                    y,
+                    height: 1,
+                    ..area
+                },
+                *id,
+            )
+        })
+        .collect()
+}